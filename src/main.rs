@@ -1,21 +1,41 @@
 use anyhow::{Context, Result};
-use clap::Parser;
+use clap::{Parser, ValueEnum};
 use colored::*;
+use flate2::{write::GzEncoder, Compression};
 use notify::{event::EventKind, Event, RecursiveMode, Watcher};
-use reqwest::{multipart, Client};
+use rand::Rng;
+use reqwest::{header::HeaderMap, multipart, Client, RequestBuilder};
+use rustyline::completion::Completer;
+use rustyline::error::ReadlineError;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::history::DefaultHistory;
+use rustyline::validate::Validator;
+use rustyline::Editor;
 use serde::Deserialize;
-use std::collections::HashMap;
-use std::io::{self, Write};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::io::{self, IsTerminal, Write};
 use std::path::{Path, PathBuf};
-use std::sync::mpsc::channel;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Exit code used when a run is stopped with Ctrl-C
+const INTERRUPTED_EXIT_CODE: i32 = 130;
+
+/// Default cap on a response body (50 MiB)
+const DEFAULT_MAX_RESPONSE_BYTES: usize = 50 * 1024 * 1024;
 
 #[derive(Parser)]
 #[command(name = "apiline")]
 #[command(about = "Interactive CLI tool for executing API workflows step-by-step")]
 #[command(version = "0.1.0")]
 struct Args {
-    /// Configuration file path
-    config: PathBuf,
+    /// Configuration file paths; requests are concatenated in order and
+    /// captured variables are saved to the first file
+    #[arg(required = true)]
+    config: Vec<PathBuf>,
 
     /// Server base URL
     #[arg(long, default_value = "http://localhost:8080")]
@@ -28,15 +48,133 @@ struct Args {
     /// Start from specific step number
     #[arg(long)]
     start_from: Option<usize>,
+
+    /// Print the request list and exit
+    #[arg(long)]
+    list: bool,
+
+    /// Content-Type sent with PATCH requests that don't set `content_type`
+    #[arg(long, default_value = "application/merge-patch+json")]
+    patch_content_type: String,
+
+    /// Gzip JSON request bodies (requests can override with `gzip`)
+    #[arg(long)]
+    gzip: bool,
+
+    /// Abort when a response body is larger than this many bytes
+    #[arg(long, value_name = "N", default_value_t = DEFAULT_MAX_RESPONSE_BYTES)]
+    max_response_bytes: usize,
+
+    /// Re-run this step number every time the config file changes
+    #[arg(long, value_name = "N")]
+    watch_step: Option<usize>,
+
+    /// Write captured variables to this file as `export NAME='value'` lines
+    #[arg(long, value_name = "PATH")]
+    export_env: Option<PathBuf>,
+
+    /// Run the named workflow without confirmation, then exit
+    #[arg(long, value_name = "NAME")]
+    workflow: Option<String>,
+
+    /// Exit non-zero if any warning was raised during load or execution
+    #[arg(long)]
+    fail_on_warning: bool,
+
+    /// Also send every executed request to this base URL and diff the responses
+    #[arg(long, value_name = "BASE_URL")]
+    compare: Option<String>,
+
+    /// Field name or `$.path` to leave out of --compare diffs (repeatable)
+    #[arg(long = "ignore-field", value_name = "FIELD")]
+    ignore_fields: Vec<String>,
+
+    /// Exit non-zero if --compare found any differences
+    #[arg(long)]
+    fail_on_diff: bool,
+
+    /// Check that the server is reachable before starting
+    #[arg(long)]
+    ping: bool,
+
+    /// Print the `depends_on` graph in Graphviz DOT format, then exit
+    #[arg(long)]
+    graph: bool,
+
+    /// Print the config with variables and defaults resolved, then exit
+    #[arg(long)]
+    dump_resolved: bool,
+
+    /// Diagnostic log filter written to stderr, e.g. `info` or `apiline=debug`
+    #[arg(long, default_value = "off")]
+    log_level: String,
+
+    /// Diagnostic log format
+    #[arg(long, value_enum, default_value_t = LogFormat::Text)]
+    log_format: LogFormat,
+
+    /// Base directory for relative file paths (defaults to each config file's directory)
+    #[arg(long, value_name = "DIR")]
+    root: Option<PathBuf>,
+
+    /// Output format for non-interactive modes
+    #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+    output: OutputFormat,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum OutputFormat {
+    Text,
+    Json,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum LogFormat {
+    Text,
+    Json,
+}
+
+/// Routes diagnostics to stderr so the interactive output on stdout stays
+/// unchanged.
+fn init_logging(level: &str, format: LogFormat) -> Result<()> {
+    let filter = tracing_subscriber::EnvFilter::try_new(level)
+        .with_context(|| format!("Invalid log level: {}", level))?;
+    let builder = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(io::stderr);
+
+    match format {
+        LogFormat::Text => builder.init(),
+        LogFormat::Json => builder.json().init(),
+    }
+    Ok(())
 }
 
 #[derive(Debug, Deserialize, serde::Serialize)]
 struct ApilineConfig {
     #[serde(default)]
     variables: HashMap<String, String>,
+    /// Auth used by requests that don't set their own
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    default_auth: Option<String>,
+    /// Checked once at startup before any request runs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    healthcheck: Option<Healthcheck>,
+    /// Named, ordered lists of request names that can be run on their own
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    workflows: HashMap<String, Vec<String>>,
     requests: Vec<ApiRequest>,
 }
 
+#[derive(Debug, Default, Deserialize, serde::Serialize, Clone)]
+struct Healthcheck {
+    /// Path appended to the base URL; empty checks the base URL itself
+    #[serde(default)]
+    endpoint: String,
+    #[serde(default = "default_status")]
+    expected_status: u16,
+}
+
 #[derive(Debug, Deserialize, serde::Serialize, Clone)]
 struct ApiRequest {
     name: String,
@@ -45,7 +183,27 @@ struct ApiRequest {
     payload: Option<serde_json::Value>,
     #[serde(default)]
     files: Option<HashMap<String, String>>,
-    auth: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    content_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    gzip: Option<bool>,
+    /// Accept header; non-JSON types keep the response body as plain text
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    accept: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    cookies: Option<HashMap<String, String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    retry: Option<RetryPolicy>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    expected_fields: Option<Vec<String>>,
+    /// Fail when the response has top-level fields outside `expected_fields`
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    strict: bool,
+    /// Send If-None-Match with the last ETag for this URL and reuse the cached body on 304
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    cache_etag: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    auth: Option<String>,
     #[serde(default = "default_status")]
     expected_status: u16,
     #[serde(default)]
@@ -54,17 +212,656 @@ struct ApiRequest {
     extract_path: Option<String>,
     #[serde(default)]
     save_multiple: Option<HashMap<String, String>>,
+    /// Variable name -> cookie name, captured from `Set-Cookie` response headers
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    save_cookies: Option<HashMap<String, String>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+    /// Names of requests this one relies on, used by `--graph`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    depends_on: Vec<String>,
+    /// Directory of the config file the request came from
+    #[serde(skip)]
+    base_dir: PathBuf,
+}
+
+impl ApiRequest {
+    fn accept(&self) -> &str {
+        self.accept.as_deref().unwrap_or("application/json")
+    }
+
+    /// Whether the response body should be parsed as JSON
+    fn expects_json(&self) -> bool {
+        self.accept().to_lowercase().contains("json")
+    }
+
+    fn auth(&self) -> Result<&str> {
+        self.auth.as_deref().ok_or_else(|| {
+            anyhow::anyhow!(
+                "Request '{}' has no auth and the config has no default_auth",
+                self.name
+            )
+        })
+    }
 }
 
 fn default_status() -> u16 {
     200
 }
 
+/// Retry settings for a request. Delays grow exponentially from
+/// `initial_backoff_ms`, are capped by `max_backoff_ms`, and retrying stops once
+/// `max_attempts` or `max_total_wait_ms` is reached.
+#[derive(Debug, Deserialize, serde::Serialize, Clone)]
+struct RetryPolicy {
+    #[serde(default = "default_max_attempts")]
+    max_attempts: u32,
+    /// Statuses that trigger a retry; empty means 429 and any 5xx
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    on_status: Vec<u16>,
+    #[serde(default = "default_initial_backoff_ms")]
+    initial_backoff_ms: u64,
+    #[serde(default = "default_max_backoff_ms")]
+    max_backoff_ms: u64,
+    #[serde(default = "default_jitter")]
+    jitter: bool,
+    #[serde(default = "default_max_total_wait_ms")]
+    max_total_wait_ms: u64,
+    /// Retry while any of these hold for the JSON response body
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    when_body: Vec<BodyCondition>,
+}
+
+/// Matches when the value at `path` in the response body equals `equals`.
+#[derive(Debug, Deserialize, serde::Serialize, Clone)]
+struct BodyCondition {
+    path: String,
+    equals: serde_json::Value,
+}
+
+fn default_max_attempts() -> u32 {
+    3
+}
+
+fn default_initial_backoff_ms() -> u64 {
+    100
+}
+
+fn default_max_backoff_ms() -> u64 {
+    10_000
+}
+
+fn default_jitter() -> bool {
+    true
+}
+
+fn default_max_total_wait_ms() -> u64 {
+    60_000
+}
+
+impl Default for RetryPolicy {
+    /// A single attempt, i.e. no retries
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 1,
+            on_status: Vec::new(),
+            initial_backoff_ms: default_initial_backoff_ms(),
+            max_backoff_ms: default_max_backoff_ms(),
+            jitter: default_jitter(),
+            max_total_wait_ms: default_max_total_wait_ms(),
+            when_body: Vec::new(),
+        }
+    }
+}
+
+impl RetryPolicy {
+    fn retries_status(&self, status: u16) -> bool {
+        if self.on_status.is_empty() {
+            status == 429 || (500..600).contains(&status)
+        } else {
+            self.on_status.contains(&status)
+        }
+    }
+
+    /// Describes the first body condition that holds, if any
+    fn matching_body_condition(&self, response_text: &str) -> Option<String> {
+        if self.when_body.is_empty() {
+            return None;
+        }
+
+        let body: serde_json::Value = serde_json::from_str(response_text).ok()?;
+        self.when_body
+            .iter()
+            .find(|condition| lookup_json_path(&body, &condition.path) == Some(&condition.equals))
+            .map(|condition| format!("{} == {}", condition.path, condition.equals))
+    }
+
+    /// Delay before the next attempt, or `None` when retries are exhausted.
+    /// `attempt` is the number of attempts made so far.
+    fn next_delay(&self, attempt: u32, waited: Duration) -> Option<Duration> {
+        if attempt >= self.max_attempts {
+            return None;
+        }
+
+        let remaining_ms = self
+            .max_total_wait_ms
+            .saturating_sub(waited.as_millis() as u64);
+        if remaining_ms == 0 {
+            return None;
+        }
+
+        let backoff_ms = self
+            .initial_backoff_ms
+            .saturating_mul(1u64 << (attempt - 1).min(63))
+            .min(self.max_backoff_ms);
+        // "Equal jitter": keep at least half of the backoff
+        let delay_ms = if self.jitter && backoff_ms > 1 {
+            rand::thread_rng().gen_range(backoff_ms / 2..=backoff_ms)
+        } else {
+            backoff_ms
+        };
+
+        Some(Duration::from_millis(delay_ms.min(remaining_ms)))
+    }
+}
+
+/// Settings shared by every request in a session, taken from the command line.
+#[derive(Clone)]
+struct RequestOptions {
+    base_url: String,
+    default_api_key: String,
+    patch_content_type: String,
+    gzip_requests: bool,
+    fail_on_warning: bool,
+    max_response_bytes: usize,
+    export_env: Option<PathBuf>,
+    /// Overrides the config file directory as the base for relative paths
+    root: Option<PathBuf>,
+    /// Second base URL every request is also sent to, for `--compare`
+    compare_base_url: Option<String>,
+    /// Fields left out of `--compare` diffs
+    ignore_fields: Vec<String>,
+}
+
+impl RequestOptions {
+    fn base_dir<'a>(&'a self, request: &'a ApiRequest) -> &'a Path {
+        self.root.as_deref().unwrap_or(&request.base_dir)
+    }
+
+    /// Explicit `content_type` wins, then the PATCH default, then plain JSON.
+    fn content_type<'a>(&'a self, request: &'a ApiRequest) -> &'a str {
+        match &request.content_type {
+            Some(content_type) => content_type,
+            None if request.method.eq_ignore_ascii_case("PATCH") => &self.patch_content_type,
+            None => "application/json",
+        }
+    }
+}
+
+/// State that outlives a single request within one apiline session.
+#[derive(Default)]
+struct Session {
+    interrupt: InterruptState,
+    /// Auth resolved for the most recently executed request, for `auth: inherit`
+    last_auth: Option<ResolvedAuth>,
+    /// Variables captured from responses during this session
+    captured: BTreeSet<String>,
+    /// Variables set by hand with `s` during this session
+    edited: BTreeSet<String>,
+    /// Reads the menu, `set` and confirmation prompts
+    line_editor: LineEditor,
+    /// Last response with an ETag per URL, for requests with `cache_etag`
+    etag_cache: HashMap<String, CachedResponse>,
+    /// Warnings from the latest config load, replaced on every reload
+    load_warnings: Vec<String>,
+    /// Non-fatal warnings raised while executing requests
+    warnings: Vec<String>,
+    /// Requests whose responses differed under `--compare`
+    differing: Vec<String>,
+}
+
+struct CachedResponse {
+    etag: String,
+    status: reqwest::StatusCode,
+    text: String,
+}
+
+impl Session {
+    /// Logs, prints and records a non-fatal warning for `--fail-on-warning`.
+    fn warn(&mut self, message: String) {
+        warn(&mut self.warnings, message);
+    }
+}
+
+/// Logs and prints a warning and records it in `warnings`. Every warning goes
+/// through here (or `Session::warn`) so `--fail-on-warning` sees all of them.
+fn warn(warnings: &mut Vec<String>, message: String) {
+    tracing::warn!("{}", message);
+    println!("{} {}", "⚠️  Warning:".yellow(), message);
+    warnings.push(message);
+}
+
+#[derive(Clone)]
+enum ResolvedAuth {
+    None,
+    Header(&'static str, String),
+}
+
+/// Shared Ctrl-C state. While `busy` is set an interrupt only flags the run
+/// so it can stop after the in-flight request; otherwise it exits immediately.
+#[derive(Clone, Default)]
+struct InterruptState {
+    busy: Arc<AtomicBool>,
+    interrupted: Arc<AtomicBool>,
+}
+
+impl InterruptState {
+    fn set_busy(&self, busy: bool) {
+        self.busy.store(busy, Ordering::SeqCst);
+    }
+
+    fn is_busy(&self) -> bool {
+        self.busy.load(Ordering::SeqCst)
+    }
+
+    fn is_interrupted(&self) -> bool {
+        self.interrupted.load(Ordering::SeqCst)
+    }
+
+    /// Records a Ctrl-C that arrived as input rather than as a signal.
+    fn interrupt(&self) {
+        self.interrupted.store(true, Ordering::SeqCst);
+    }
+}
+
+fn install_interrupt_handler(state: InterruptState) {
+    tokio::spawn(async move {
+        while tokio::signal::ctrl_c().await.is_ok() {
+            if state.busy.load(Ordering::SeqCst) && !state.interrupted.swap(true, Ordering::SeqCst)
+            {
+                println!(
+                    "\n{}",
+                    "⚠️  Interrupt received, stopping after the current request (Ctrl-C again to force quit)"
+                        .yellow()
+                );
+            } else {
+                println!("\n{}", "Interrupted. Goodbye! 👋".yellow());
+                std::process::exit(INTERRUPTED_EXIT_CODE);
+            }
+        }
+    });
+}
+
+#[derive(Default)]
+struct RunSummary {
+    completed: usize,
+    skipped: usize,
+    failed: usize,
+}
+
+impl RunSummary {
+    fn print(&self, title: &str) {
+        println!(
+            "\n{} {} completed, {} skipped, {} failed",
+            title.bold(),
+            self.completed.to_string().green(),
+            self.skipped.to_string().yellow(),
+            self.failed.to_string().red()
+        );
+    }
+}
+
 fn load_config(config_path: &Path) -> Result<ApilineConfig> {
     let config_content = std::fs::read_to_string(config_path)
         .with_context(|| format!("Failed to read config: {:?}", config_path))?;
 
-    serde_yaml::from_str(&config_content).context("Failed to parse YAML config")
+    let mut config: ApilineConfig = serde_yaml::from_str(&config_content)
+        .with_context(|| format!("Failed to parse YAML config: {:?}", config_path))?;
+
+    // Relative file paths in a request resolve against its own config file
+    let base_dir = match config_path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
+    for request in &mut config.requests {
+        request.base_dir = base_dir.clone();
+        if request.strict && request.expected_fields.is_none() {
+            return Err(anyhow::anyhow!(
+                "Request '{}' sets 'strict' without 'expected_fields' in {:?}",
+                request.name,
+                config_path
+            ));
+        }
+
+        let extract_paths = request.extract_path.iter().chain(
+            request
+                .save_multiple
+                .iter()
+                .flat_map(|paths| paths.values()),
+        );
+        for path in extract_paths {
+            validate_extract_path(path).map_err(|e| {
+                anyhow::anyhow!(
+                    "Request '{}' has an invalid extract path '{}' in {:?}: {}",
+                    request.name,
+                    path,
+                    config_path,
+                    e
+                )
+            })?;
+        }
+    }
+
+    Ok(config)
+}
+
+/// Loads every config file and merges them into one: requests are
+/// concatenated in order and variables from later files win.
+fn load_configs(config_paths: &[PathBuf]) -> Result<ApilineConfig> {
+    let (first, rest) = config_paths
+        .split_first()
+        .ok_or_else(|| anyhow::anyhow!("No config file given"))?;
+
+    let mut config = load_config(first)?;
+    for config_path in rest {
+        let included = load_config(config_path)?;
+        config.variables.extend(included.variables);
+        if included.default_auth.is_some() {
+            config.default_auth = included.default_auth;
+        }
+        if included.healthcheck.is_some() {
+            config.healthcheck = included.healthcheck;
+        }
+        config.workflows.extend(included.workflows);
+        config.requests.extend(included.requests);
+    }
+
+    Ok(config)
+}
+
+/// Writes the variables named in `changed` (captured or set this session) into
+/// the config file. Everything else stays as it is on disk, so values that a
+/// later merged file overrides never leak into this one.
+fn save_variables(
+    config_path: &Path,
+    variables: &HashMap<String, String>,
+    changed: &BTreeSet<String>,
+) -> Result<()> {
+    let mut config = load_config(config_path)?;
+    for name in changed {
+        if let Some(value) = variables.get(name) {
+            config.variables.insert(name.clone(), value.clone());
+        }
+    }
+    save_config(config_path, &config)
+}
+
+/// Prints the `depends_on` graph in Graphviz DOT format. Unknown dependency
+/// names and cycles are reported as errors.
+fn print_dependency_graph(requests: &[ApiRequest]) -> Result<()> {
+    let mut index: HashMap<&str, usize> = HashMap::new();
+    for (i, request) in requests.iter().enumerate() {
+        index.entry(request.name.as_str()).or_insert(i);
+    }
+
+    // dependencies[i] lists the requests that request i depends on
+    let mut dependencies: Vec<Vec<usize>> = Vec::with_capacity(requests.len());
+    for request in requests {
+        let mut deps = Vec::with_capacity(request.depends_on.len());
+        for name in &request.depends_on {
+            let dep = index.get(name.as_str()).ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Request '{}' depends on unknown request '{}'",
+                    request.name,
+                    name
+                )
+            })?;
+            deps.push(*dep);
+        }
+        dependencies.push(deps);
+    }
+
+    if let Some(cycle) = find_dependency_cycle(&dependencies) {
+        let names: Vec<&str> = cycle.iter().map(|&i| requests[i].name.as_str()).collect();
+        return Err(anyhow::anyhow!("Dependency cycle: {}", names.join(" -> ")));
+    }
+
+    let escape = |s: &str| s.replace('\\', "\\\\").replace('"', "\\\"");
+    println!("digraph apiline {{");
+    println!("  rankdir=LR;");
+    for (i, request) in requests.iter().enumerate() {
+        println!(
+            "  n{} [label=\"{}. {}\"];",
+            i + 1,
+            i + 1,
+            escape(&request.name)
+        );
+    }
+    for (i, deps) in dependencies.iter().enumerate() {
+        for dep in deps {
+            println!("  n{} -> n{};", dep + 1, i + 1);
+        }
+    }
+    println!("}}");
+
+    Ok(())
+}
+
+/// Returns the requests forming a cycle, starting and ending at the same one.
+fn find_dependency_cycle(dependencies: &[Vec<usize>]) -> Option<Vec<usize>> {
+    #[derive(Clone, Copy, PartialEq)]
+    enum Mark {
+        Unvisited,
+        InProgress,
+        Done,
+    }
+
+    fn visit(
+        node: usize,
+        dependencies: &[Vec<usize>],
+        marks: &mut [Mark],
+        path: &mut Vec<usize>,
+    ) -> Option<Vec<usize>> {
+        marks[node] = Mark::InProgress;
+        path.push(node);
+
+        for &dep in &dependencies[node] {
+            match marks[dep] {
+                Mark::InProgress => {
+                    let start = path.iter().position(|&n| n == dep).unwrap_or(0);
+                    let mut cycle = path[start..].to_vec();
+                    cycle.push(dep);
+                    return Some(cycle);
+                }
+                Mark::Unvisited => {
+                    if let Some(cycle) = visit(dep, dependencies, marks, path) {
+                        return Some(cycle);
+                    }
+                }
+                Mark::Done => {}
+            }
+        }
+
+        path.pop();
+        marks[node] = Mark::Done;
+        None
+    }
+
+    let mut marks = vec![Mark::Unvisited; dependencies.len()];
+    (0..dependencies.len()).find_map(|node| {
+        if marks[node] == Mark::Unvisited {
+            visit(node, dependencies, &mut marks, &mut Vec::new())
+        } else {
+            None
+        }
+    })
+}
+
+/// Builds the config as apiline would send it before any responses are
+/// captured: default auth applied and variables substituted, with secret
+/// variables and literal credentials masked.
+fn resolve_config_for_dump(config: &ApilineConfig) -> Result<ApilineConfig> {
+    const MASK: &str = "****";
+
+    let variables: HashMap<String, String> = config
+        .variables
+        .iter()
+        .map(|(name, value)| {
+            let value = if is_secret_variable(name) {
+                MASK.to_string()
+            } else {
+                value.clone()
+            };
+            (name.clone(), value)
+        })
+        .collect();
+
+    let mut requests = Vec::with_capacity(config.requests.len());
+    for request in &config.requests {
+        let mut request = request.clone();
+
+        request.auth = request
+            .auth
+            .or_else(|| config.default_auth.clone())
+            .map(|auth| {
+                if auth.starts_with("Bearer ") {
+                    format!("Bearer {}", MASK)
+                } else if auth.starts_with("api-key:") {
+                    format!("api-key:{}", MASK)
+                } else {
+                    auth
+                }
+            });
+        request.endpoint = substitute_variables_in_string(&request.endpoint, &variables);
+        if let Some(payload) = &mut request.payload {
+            substitute_variables(payload, &variables)?;
+        }
+        for values in [&mut request.files, &mut request.cookies]
+            .into_iter()
+            .flatten()
+        {
+            for value in values.values_mut() {
+                *value = substitute_variables_in_string(value, &variables);
+            }
+        }
+
+        requests.push(request);
+    }
+
+    Ok(ApilineConfig {
+        variables,
+        default_auth: None,
+        healthcheck: config.healthcheck.clone(),
+        workflows: config.workflows.clone(),
+        requests,
+    })
+}
+
+/// Warns about `${...}` references that are neither declared under `variables`
+/// nor captured by an earlier request's `save_as`/`save_multiple`.
+fn warn_undefined_variables(config: &ApilineConfig, warnings: &mut Vec<String>) {
+    let mut known: HashSet<&str> = config.variables.keys().map(String::as_str).collect();
+
+    for request in &config.requests {
+        let mut references = find_placeholders(&request.endpoint);
+        if let Some(payload) = &request.payload {
+            collect_value_placeholders(payload, &mut references);
+        }
+        if let Some(files) = &request.files {
+            for file_path in files.values() {
+                references.extend(find_placeholders(file_path));
+            }
+        }
+        if let Some(cookies) = &request.cookies {
+            for value in cookies.values() {
+                references.extend(find_placeholders(value));
+            }
+        }
+
+        let mut reported = HashSet::new();
+        for name in references {
+            if !known.contains(name) && reported.insert(name) {
+                warn(
+                    warnings,
+                    format!("{}: ${{{}}} is not defined", request.name, name),
+                );
+            }
+        }
+
+        known.extend(request.save_as.as_deref());
+        if let Some(save_multiple) = &request.save_multiple {
+            known.extend(save_multiple.keys().map(String::as_str));
+        }
+        if let Some(save_cookies) = &request.save_cookies {
+            known.extend(save_cookies.keys().map(String::as_str));
+        }
+    }
+}
+
+/// Names referenced as `${name}` in a string. Placeholders with a
+/// `${name:-default}` fallback always resolve and are skipped.
+fn find_placeholders(s: &str) -> Vec<&str> {
+    let mut names = Vec::new();
+    let mut rest = s;
+
+    while let Some(start) = rest.find("${") {
+        let after = &rest[start + 2..];
+        match after.find('}') {
+            Some(end) => {
+                if let (name, None) = split_placeholder(&after[..end]) {
+                    names.push(name);
+                }
+                rest = &after[end + 1..];
+            }
+            None => break,
+        }
+    }
+
+    names
+}
+
+/// Splits the inside of a placeholder into the variable name and its
+/// `:-default`, if any.
+fn split_placeholder(inner: &str) -> (&str, Option<&str>) {
+    match inner.split_once(":-") {
+        Some((name, default)) => (name, Some(default)),
+        None => (inner, None),
+    }
+}
+
+/// `${name}` references in a payload from the config that no variable
+/// resolves. Checked before substitution, since substituted values may
+/// themselves contain `${...}`.
+fn unresolved_placeholders(
+    payload: &serde_json::Value,
+    variables: &HashMap<String, String>,
+) -> Vec<String> {
+    let mut referenced = Vec::new();
+    collect_value_placeholders(payload, &mut referenced);
+    referenced
+        .into_iter()
+        .filter(|name| !variables.contains_key(*name))
+        .map(|name| format!("${{{}}}", name))
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect()
+}
+
+fn collect_value_placeholders<'a>(value: &'a serde_json::Value, names: &mut Vec<&'a str>) {
+    match value {
+        serde_json::Value::String(s) => names.extend(find_placeholders(s)),
+        serde_json::Value::Object(map) => {
+            for v in map.values() {
+                collect_value_placeholders(v, names);
+            }
+        }
+        serde_json::Value::Array(arr) => {
+            for item in arr {
+                collect_value_placeholders(item, names);
+            }
+        }
+        _ => {}
+    }
 }
 
 fn save_config(config_path: &Path, config: &ApilineConfig) -> Result<()> {
@@ -88,238 +885,964 @@ fn save_config(config_path: &Path, config: &ApilineConfig) -> Result<()> {
             first_request = false;
         }
 
-        formatted_lines.push(line.to_string());
+        formatted_lines.push(line.to_string());
+    }
+
+    let formatted_content = formatted_lines.join("\n");
+
+    std::fs::write(config_path, formatted_content)
+        .with_context(|| format!("Failed to write config to {:?}", config_path))?;
+
+    Ok(())
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let args = Args::parse();
+    init_logging(&args.log_level, args.log_format)?;
+
+    // Load initial config
+    let config_paths = &args.config;
+    let config_path = config_paths[0].as_path();
+    let mut config = load_configs(config_paths)?;
+
+    if args.list {
+        let current_step = args.start_from.unwrap_or(0);
+        match args.output {
+            OutputFormat::Text => list_requests(&config.requests, current_step),
+            OutputFormat::Json => print_requests_json(&config.requests)?,
+        }
+        return Ok(());
+    }
+
+    if args.graph {
+        return print_dependency_graph(&config.requests);
+    }
+
+    if args.dump_resolved {
+        let resolved = resolve_config_for_dump(&config)?;
+        let dump = match args.output {
+            OutputFormat::Text => serde_yaml::to_string(&resolved)
+                .context("Failed to serialize resolved config to YAML")?,
+            OutputFormat::Json => serde_json::to_string_pretty(&resolved)
+                .context("Failed to serialize resolved config to JSON")?,
+        };
+        println!("{}", dump);
+        return Ok(());
+    }
+
+    let mut session = Session::default();
+    install_interrupt_handler(session.interrupt.clone());
+
+    warn_undefined_variables(&config, &mut session.load_warnings);
+
+    // Set up file watcher for hot reloading
+    let (tx, rx) = channel();
+    let mut watcher = notify::recommended_watcher(move |res: Result<Event, notify::Error>| {
+        if let Ok(event) = res {
+            if matches!(event.kind, EventKind::Modify(_)) {
+                let _ = tx.send(());
+            }
+        }
+    })
+    .context("Failed to create file watcher")?;
+
+    for path in config_paths {
+        watcher
+            .watch(path, RecursiveMode::NonRecursive)
+            .with_context(|| format!("Failed to watch config file: {:?}", path))?;
+    }
+
+    let client = Client::new();
+    let options = RequestOptions {
+        base_url: args.base_url,
+        default_api_key: args.api_key,
+        patch_content_type: args.patch_content_type,
+        gzip_requests: args.gzip,
+        fail_on_warning: args.fail_on_warning,
+        max_response_bytes: args.max_response_bytes,
+        export_env: args.export_env,
+        root: args.root,
+        compare_base_url: args.compare,
+        ignore_fields: args.ignore_fields,
+    };
+
+    println!(
+        "{}",
+        "🚀 APIline - Interactive API Workflow Tool".bold().blue()
+    );
+    println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
+    for path in config_paths {
+        println!("{}", format!("📁 Watching: {:?}", path).dimmed());
+    }
+    println!("{}", "💡 Config will auto-reload on file changes".dimmed());
+
+    let healthcheck = match (&config.healthcheck, args.ping) {
+        (Some(healthcheck), _) => Some(healthcheck.clone()),
+        (None, true) => Some(Healthcheck {
+            endpoint: String::new(),
+            expected_status: default_status(),
+        }),
+        (None, false) => None,
+    };
+    if let Some(healthcheck) = healthcheck {
+        run_healthcheck(&client, &options.base_url, &healthcheck).await?;
+    }
+
+    if let Some(name) = &args.workflow {
+        let completed = run_workflow(
+            &client,
+            &options,
+            &mut session,
+            &mut config,
+            config_path,
+            name,
+            true,
+        )
+        .await?;
+        check_warnings(&session, &options);
+        check_differences(&session, args.fail_on_diff);
+        if !completed {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    if let Some(step_num) = args.watch_step {
+        return watch_step(
+            &client,
+            &options,
+            &mut session,
+            &mut config,
+            config_paths,
+            step_num,
+            &rx,
+        )
+        .await;
+    }
+
+    let mut current_step = args.start_from.unwrap_or(0);
+    // Set when an `all` run stops on an error, remembering its confirmation mode
+    let mut resume_confirmations: Option<bool> = None;
+
+    loop {
+        // Check for config file changes (non-blocking)
+        if let Ok(()) = rx.try_recv() {
+            reload_config(config_paths, &mut config, &mut session.load_warnings);
+        }
+        // A Ctrl-C during a single step lets it finish, then exits here
+        if session.interrupt.is_interrupted() {
+            println!("{}", "Interrupted. Goodbye! 👋".yellow());
+            exit_interrupted(&session, &options);
+        }
+
+        // Show menu
+        show_menu(&config, current_step)?;
+
+        // Get user input
+        let mut candidates: Vec<String> = config
+            .requests
+            .iter()
+            .map(|request| request.name.clone())
+            .collect();
+        candidates.extend(config.workflows.keys().map(|name| format!("w {}", name)));
+        let input = match session.line_editor.read_line(
+            &format!("\n{} ", "Choose option:".bold()),
+            candidates,
+            &config.variables,
+        )? {
+            Input::Line(input) => input,
+            // End of input behaves like `q`
+            Input::Eof => {
+                println!("\n{}", "Goodbye! 👋".green());
+                break;
+            }
+            Input::Interrupted => {
+                println!("{}", "Interrupted. Goodbye! 👋".yellow());
+                exit_interrupted(&session, &options);
+            }
+        };
+        let choice = input.trim();
+
+        match choice {
+            "v" | "vars" => {
+                show_variables(&config.variables);
+            }
+            "l" | "list" => {
+                list_requests(&config.requests, current_step);
+            }
+            "s" | "set" => {
+                set_variable(
+                    &mut session.line_editor,
+                    &mut config.variables,
+                    &mut session.edited,
+                )?;
+            }
+            "q" | "quit" => {
+                println!("{}", "Goodbye! 👋".green());
+                break;
+            }
+            "n" | "next" => {
+                if current_step < config.requests.len() {
+                    session.interrupt.set_busy(true);
+                    let result = execute_request_with_option(
+                        &client,
+                        &options,
+                        &mut session,
+                        &mut config,
+                        config_path,
+                        current_step,
+                        false,
+                    )
+                    .await;
+                    session.interrupt.set_busy(false);
+
+                    match result {
+                        Ok(true) => {
+                            current_step += 1;
+                            if current_step >= config.requests.len() {
+                                println!("\n{}", "✅ All requests completed!".bold().green());
+                            }
+                        }
+                        // Already reported; the loop exits on the next pass
+                        Ok(false) if session.interrupt.is_interrupted() => {}
+                        Ok(false) => {
+                            println!("{}", "Request cancelled".yellow());
+                        }
+                        Err(e) => {
+                            println!("{} {}", "❌ Error:".red(), e);
+                        }
+                    }
+                } else {
+                    println!("{}", "No more requests to execute".yellow());
+                }
+            }
+            "w" | "workflows" => {
+                list_workflows(&config);
+            }
+            workflow if workflow.starts_with("w ") => {
+                let name = workflow["w ".len()..].trim();
+                if !config.workflows.contains_key(name) {
+                    println!("{} Unknown workflow: {}", "❌ Error:".red(), name);
+                    continue;
+                }
+                print!(
+                    "{} [Y/n]: ",
+                    "Execute the workflow without confirmation?".bold()
+                );
+                io::stdout().flush()?;
+
+                let mut confirm_all = String::new();
+                io::stdin().read_line(&mut confirm_all)?;
+                let skip_confirmations = confirm_all.trim().to_lowercase() != "n"
+                    && confirm_all.trim().to_lowercase() != "no";
+
+                if let Err(e) = run_workflow(
+                    &client,
+                    &options,
+                    &mut session,
+                    &mut config,
+                    config_path,
+                    name,
+                    skip_confirmations,
+                )
+                .await
+                {
+                    println!("{} {}", "❌ Error:".red(), e);
+                }
+            }
+            "a" | "all" => {
+                print!(
+                    "{} [Y/n]: ",
+                    "Execute all remaining requests without confirmation?".bold()
+                );
+                io::stdout().flush()?;
+
+                let mut confirm_all = String::new();
+                io::stdin().read_line(&mut confirm_all)?;
+                let skip_confirmations = confirm_all.trim().to_lowercase() != "n"
+                    && confirm_all.trim().to_lowercase() != "no";
+
+                println!("{}", "Executing all remaining requests...".blue());
+                let completed = run_remaining(
+                    &client,
+                    &options,
+                    &mut session,
+                    &mut config,
+                    config_path,
+                    &mut current_step,
+                    skip_confirmations,
+                )
+                .await;
+                resume_confirmations = (!completed).then_some(skip_confirmations);
+            }
+            "r" | "resume" => match resume_confirmations {
+                Some(skip_confirmations) => {
+                    println!(
+                        "{}",
+                        format!("Resuming from step {}...", current_step + 1).blue()
+                    );
+                    let completed = run_remaining(
+                        &client,
+                        &options,
+                        &mut session,
+                        &mut config,
+                        config_path,
+                        &mut current_step,
+                        skip_confirmations,
+                    )
+                    .await;
+                    resume_confirmations = (!completed).then_some(skip_confirmations);
+                }
+                None => {
+                    println!(
+                        "{}",
+                        "Nothing to resume. Use 'a' to execute all remaining requests.".yellow()
+                    );
+                }
+            },
+            _ => {
+                // Try to parse as step number, then as a request name or prefix
+                let step_index = match choice.parse::<usize>() {
+                    Ok(step_num) if step_num > 0 && step_num <= config.requests.len() => {
+                        Some(step_num - 1)
+                    }
+                    Ok(_) => {
+                        println!("{}", "Invalid step number".red());
+                        None
+                    }
+                    Err(_) => match find_step_by_name(&config.requests, choice) {
+                        StepMatch::Found(step_index) => Some(step_index),
+                        StepMatch::Ambiguous(candidates) => {
+                            println!(
+                                "{}",
+                                format!("'{}' matches several requests:", choice).yellow()
+                            );
+                            for i in candidates {
+                                println!(
+                                    "  {}: {}",
+                                    format!("{:2}", i + 1).bold(),
+                                    config.requests[i].name.green()
+                                );
+                            }
+                            None
+                        }
+                        StepMatch::NotFound => {
+                            println!(
+                                "{}",
+                                "Invalid option. Try 'v', 's', 'l', 'n', 'a', 'r', 'w', a step number, or a request name."
+                                    .red()
+                            );
+                            None
+                        }
+                    },
+                };
+
+                if let Some(step_index) = step_index {
+                    session.interrupt.set_busy(true);
+                    let result = execute_request_with_option(
+                        &client,
+                        &options,
+                        &mut session,
+                        &mut config,
+                        config_path,
+                        step_index,
+                        false,
+                    )
+                    .await;
+                    session.interrupt.set_busy(false);
+
+                    match result {
+                        Ok(true) => {
+                            println!("{}", "✅ Request completed successfully".green());
+                            // Update current step if we executed the next one
+                            if step_index == current_step {
+                                current_step += 1;
+                            }
+                        }
+                        // Already reported; the loop exits on the next pass
+                        Ok(false) if session.interrupt.is_interrupted() => {}
+                        Ok(false) => {
+                            println!("{}", "Request cancelled".yellow());
+                        }
+                        Err(e) => {
+                            println!("{} {}", "❌ Error:".red(), e);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    check_warnings(&session, &options);
+    check_differences(&session, args.fail_on_diff);
+    Ok(())
+}
+
+/// With `--fail-on-warning`, prints every warning raised during the session
+/// and exits non-zero if there were any.
+fn check_warnings(session: &Session, options: &RequestOptions) {
+    if options.fail_on_warning && print_warnings(session) {
+        println!("{}", "Failing because of --fail-on-warning".red());
+        std::process::exit(1);
+    }
+}
+
+/// Lists the current load and execution warnings. Returns false when there
+/// were none.
+fn print_warnings(session: &Session) -> bool {
+    let count = session.load_warnings.len() + session.warnings.len();
+    if count == 0 {
+        return false;
+    }
+
+    println!("\n{}", format!("⚠️  {} warning(s):", count).bold().yellow());
+    for warning in session.load_warnings.iter().chain(&session.warnings) {
+        println!("  - {}", warning);
+    }
+    true
+}
+
+/// Exits after a Ctrl-C, still listing warnings under `--fail-on-warning`.
+fn exit_interrupted(session: &Session, options: &RequestOptions) -> ! {
+    if options.fail_on_warning {
+        print_warnings(session);
+    }
+    std::process::exit(INTERRUPTED_EXIT_CODE);
+}
+
+/// With `--fail-on-diff`, lists the requests whose `--compare` responses
+/// differed and exits non-zero if there were any.
+fn check_differences(session: &Session, fail_on_diff: bool) {
+    if !fail_on_diff || session.differing.is_empty() {
+        return;
+    }
+
+    println!(
+        "\n{}",
+        format!("🔀 {} request(s) differed:", session.differing.len())
+            .bold()
+            .yellow()
+    );
+    for name in &session.differing {
+        println!("  - {}", name);
+    }
+    println!("{}", "Failing because of --fail-on-diff".red());
+    std::process::exit(1);
+}
+
+enum StepMatch {
+    Found(usize),
+    Ambiguous(Vec<usize>),
+    NotFound,
+}
+
+/// Resolves a menu choice to a request by exact name or unique prefix,
+/// ignoring case.
+fn find_step_by_name(requests: &[ApiRequest], choice: &str) -> StepMatch {
+    if choice.is_empty() {
+        return StepMatch::NotFound;
+    }
+
+    let choice = choice.to_lowercase();
+    if let Some(i) = requests
+        .iter()
+        .position(|request| request.name.to_lowercase() == choice)
+    {
+        return StepMatch::Found(i);
     }
 
-    let formatted_content = formatted_lines.join("\n");
+    let candidates: Vec<usize> = requests
+        .iter()
+        .enumerate()
+        .filter(|(_, request)| request.name.to_lowercase().starts_with(&choice))
+        .map(|(i, _)| i)
+        .collect();
+
+    match candidates.as_slice() {
+        [] => StepMatch::NotFound,
+        [i] => StepMatch::Found(*i),
+        _ => StepMatch::Ambiguous(candidates),
+    }
+}
 
-    std::fs::write(config_path, formatted_content)
-        .with_context(|| format!("Failed to write config to {:?}", config_path))?;
+/// Fails with a readable error when the server can't be reached or answers
+/// with an unexpected status.
+async fn run_healthcheck(client: &Client, base_url: &str, healthcheck: &Healthcheck) -> Result<()> {
+    let url = format!("{}{}", base_url, healthcheck.endpoint);
+    println!("{}", format!("🩺 Checking {}...", url).dimmed());
 
-    Ok(())
-}
+    let response = client
+        .get(&url)
+        .timeout(Duration::from_secs(10))
+        .send()
+        .await
+        .map_err(|e| anyhow::anyhow!("Server is unreachable at {}: {}", url, e))?;
 
-#[tokio::main]
-async fn main() -> Result<()> {
-    let args = Args::parse();
+    let status = response.status();
+    if status.as_u16() != healthcheck.expected_status {
+        return Err(anyhow::anyhow!(
+            "Healthcheck failed: expected status {} from {}, got {}",
+            healthcheck.expected_status,
+            url,
+            status
+        ));
+    }
 
-    // Load initial config
-    let mut config = load_config(&args.config)?;
+    println!("{}", format!("✅ Server is up ({})", status).green());
+    Ok(())
+}
 
-    // Set up file watcher for hot reloading
-    let (tx, rx) = channel();
-    let mut watcher = notify::recommended_watcher(move |res: Result<Event, notify::Error>| {
-        if let Ok(event) = res {
-            if matches!(event.kind, EventKind::Modify(_)) {
-                let _ = tx.send(());
+/// Reloads the config after a file change, keeping runtime variables the new
+/// file doesn't define. Returns false, keeping the previous config, when the
+/// new one fails to load.
+fn reload_config(
+    config_paths: &[PathBuf],
+    config: &mut ApilineConfig,
+    load_warnings: &mut Vec<String>,
+) -> bool {
+    println!("\n{}", "🔄 Config file changed, reloading...".yellow());
+
+    match load_configs(config_paths) {
+        Ok(new_config) => {
+            // Preserve runtime variables
+            let old_variables = std::mem::replace(config, new_config).variables;
+
+            // Merge old runtime variables with new config
+            for (key, value) in old_variables {
+                config.variables.entry(key).or_insert(value);
             }
-        }
-    })
-    .context("Failed to create file watcher")?;
 
-    watcher
-        .watch(&args.config, RecursiveMode::NonRecursive)
-        .context("Failed to watch config file")?;
-
-    let client = Client::new();
-    let base_url = args.base_url;
-    let default_api_key = args.api_key;
+            println!("{}", "✅ Config reloaded successfully!".green());
+            load_warnings.clear();
+            warn_undefined_variables(config, load_warnings);
+            println!(
+                "{}",
+                "   Variables from previous session preserved".dimmed()
+            );
+            true
+        }
+        Err(e) => {
+            println!("{}", format!("❌ Failed to reload config: {}", e).red());
+            println!("{}", "   Continuing with previous config".yellow());
+            false
+        }
+    }
+}
 
+/// Runs a single step without confirmation, then again after every config
+/// change, until interrupted.
+async fn watch_step(
+    client: &Client,
+    options: &RequestOptions,
+    session: &mut Session,
+    config: &mut ApilineConfig,
+    config_paths: &[PathBuf],
+    step_num: usize,
+    rx: &Receiver<()>,
+) -> Result<()> {
+    let config_path = &config_paths[0];
     println!(
         "{}",
-        "🚀 APIline - Interactive API Workflow Tool".bold().blue()
+        format!(
+            "👀 Re-running step {} on every save (Ctrl-C to stop)",
+            step_num
+        )
+        .dimmed()
     );
-    println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
-    println!("{}", format!("📁 Watching: {:?}", args.config).dimmed());
-    println!("{}", "💡 Config will auto-reload on file changes".dimmed());
-
-    let mut current_step = args.start_from.unwrap_or(0);
 
     loop {
-        // Check for config file changes (non-blocking)
-        if let Ok(()) = rx.try_recv() {
-            println!("\n{}", "🔄 Config file changed, reloading...".yellow());
+        if step_num == 0 || step_num > config.requests.len() {
+            println!(
+                "{}",
+                format!(
+                    "❌ Step {} does not exist ({} requests loaded)",
+                    step_num,
+                    config.requests.len()
+                )
+                .red()
+            );
+        } else {
+            session.interrupt.set_busy(true);
+            let result = execute_request_with_option(
+                client,
+                options,
+                session,
+                config,
+                config_path,
+                step_num - 1,
+                true,
+            )
+            .await;
+            session.interrupt.set_busy(false);
+
+            match result {
+                Ok(_) => println!("{}", "✅ Request completed successfully".green()),
+                Err(e) => println!("{} {}", "❌ Error:".red(), e),
+            }
 
-            match load_config(&args.config) {
-                Ok(new_config) => {
-                    // Preserve runtime variables
-                    let old_variables = config.variables.clone();
-                    config = new_config;
+            if session.interrupt.is_interrupted() {
+                println!("{}", "Interrupted. Goodbye! 👋".yellow());
+                exit_interrupted(session, options);
+            }
+        }
 
-                    // Merge old runtime variables with new config
-                    for (key, value) in old_variables {
-                        config.variables.entry(key).or_insert(value);
+        // Ignore events from saving captured variables back to the config
+        drain_watch_events(rx).await;
+
+        // Only re-run once the file on disk loads, so the response always
+        // matches what was saved
+        loop {
+            println!("\n{}", "⏳ Waiting for config changes...".dimmed());
+            // Poll so a Ctrl-C while waiting still exits through `exit_interrupted`
+            session.interrupt.set_busy(true);
+            loop {
+                match rx.recv_timeout(Duration::from_millis(200)) {
+                    Ok(()) => break,
+                    Err(RecvTimeoutError::Timeout) if session.interrupt.is_interrupted() => {
+                        println!("{}", "Interrupted. Goodbye! 👋".yellow());
+                        exit_interrupted(session, options);
+                    }
+                    Err(RecvTimeoutError::Timeout) => {}
+                    Err(RecvTimeoutError::Disconnected) => {
+                        return Err(anyhow::anyhow!("File watcher stopped"));
                     }
-
-                    println!("{}", "✅ Config reloaded successfully!".green());
-                    println!(
-                        "{}",
-                        "   Variables from previous session preserved".dimmed()
-                    );
-                }
-                Err(e) => {
-                    println!("{}", format!("❌ Failed to reload config: {}", e).red());
-                    println!("{}", "   Continuing with previous config".yellow());
                 }
             }
+            session.interrupt.set_busy(false);
+
+            // Editors often emit several events per save
+            drain_watch_events(rx).await;
+
+            if reload_config(config_paths, config, &mut session.load_warnings) {
+                break;
+            }
         }
-        // Show menu
-        show_menu(&config, current_step)?;
+    }
+}
 
-        // Get user input
-        print!("\n{} ", "Choose option:".bold());
-        io::stdout().flush()?;
+async fn drain_watch_events(rx: &Receiver<()>) {
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    while rx.try_recv().is_ok() {}
+}
 
-        let mut input = String::new();
-        io::stdin().read_line(&mut input)?;
-        let choice = input.trim();
+fn list_workflows(config: &ApilineConfig) {
+    println!("\n{}", "🧩 Workflows:".bold().cyan());
+    println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
 
-        match choice {
-            "v" | "vars" => {
-                show_variables(&config.variables);
-            }
-            "l" | "list" => {
-                list_requests(&config.requests, current_step);
-            }
-            "s" | "set" => {
-                set_variable(&mut config.variables)?;
+    if config.workflows.is_empty() {
+        println!("  {}", "No workflows defined".dimmed());
+        return;
+    }
+
+    let mut names: Vec<&String> = config.workflows.keys().collect();
+    names.sort();
+    for name in names {
+        println!(
+            "  {}: {}",
+            name.yellow(),
+            config.workflows[name].join(" → ").green()
+        );
+    }
+    println!("\n  {}", "Run one with 'w <name>'".dimmed());
+}
+
+/// Executes the requests of a named workflow in order, stopping at the first
+/// failure. Returns false when a step failed.
+async fn run_workflow(
+    client: &Client,
+    options: &RequestOptions,
+    session: &mut Session,
+    config: &mut ApilineConfig,
+    config_path: &Path,
+    name: &str,
+    skip_confirmations: bool,
+) -> Result<bool> {
+    let request_names = config
+        .workflows
+        .get(name)
+        .ok_or_else(|| anyhow::anyhow!("Unknown workflow: {}", name))?;
+
+    let mut steps = Vec::with_capacity(request_names.len());
+    for request_name in request_names {
+        let step_index = config
+            .requests
+            .iter()
+            .position(|request| &request.name == request_name)
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "Workflow '{}' references unknown request '{}'",
+                    name,
+                    request_name
+                )
+            })?;
+        steps.push(step_index);
+    }
+
+    println!("{}", format!("Executing workflow '{}'...", name).blue());
+    let interrupt = session.interrupt.clone();
+    let mut summary = RunSummary::default();
+    interrupt.set_busy(true);
+    for (i, &step_index) in steps.iter().enumerate() {
+        if interrupt.is_interrupted() {
+            break;
+        }
+
+        println!(
+            "\n{}",
+            format!("Workflow step {}/{}", i + 1, steps.len()).bold()
+        );
+        match execute_request_with_option(
+            client,
+            options,
+            session,
+            config,
+            config_path,
+            step_index,
+            skip_confirmations,
+        )
+        .await
+        {
+            Ok(true) => summary.completed += 1,
+            // Cancelled at the confirmation prompt by Ctrl-C
+            Ok(false) if interrupt.is_interrupted() => break,
+            Ok(false) => {
+                println!("{}", "Request skipped".yellow());
+                summary.skipped += 1;
             }
-            "q" | "quit" => {
-                println!("{}", "Goodbye! 👋".green());
+            Err(e) => {
+                summary.failed += 1;
+                println!("{} {}", "❌ Error:".red(), e);
+                println!("{}", "Stopping workflow.".yellow());
                 break;
             }
-            "n" | "next" => {
-                if current_step < config.requests.len() {
-                    match execute_request_with_option(
-                        &client,
-                        &base_url,
-                        &default_api_key,
-                        &mut config,
-                        &args.config,
-                        current_step,
-                        false,
-                    )
-                    .await
-                    {
-                        Ok(true) => {
-                            current_step += 1;
-                            if current_step >= config.requests.len() {
-                                println!("\n{}", "✅ All requests completed!".bold().green());
-                            }
-                        }
-                        Ok(false) => {
-                            println!("{}", "Request cancelled".yellow());
-                        }
-                        Err(e) => {
-                            println!("{} {}", "❌ Error:".red(), e);
-                        }
-                    }
-                } else {
-                    println!("{}", "No more requests to execute".yellow());
-                }
+        }
+    }
+    interrupt.set_busy(false);
+
+    if interrupt.is_interrupted() {
+        summary.print("⏹️  Interrupted - partial summary:");
+        exit_interrupted(session, options);
+    }
+
+    summary.print("📊 Summary:");
+    Ok(summary.failed == 0)
+}
+
+/// Executes requests from `current_step` to the end, stopping at the first
+/// failure. Returns false when the run stopped on an error.
+async fn run_remaining(
+    client: &Client,
+    options: &RequestOptions,
+    session: &mut Session,
+    config: &mut ApilineConfig,
+    config_path: &Path,
+    current_step: &mut usize,
+    skip_confirmations: bool,
+) -> bool {
+    let interrupt = session.interrupt.clone();
+    let mut summary = RunSummary::default();
+    interrupt.set_busy(true);
+    while *current_step < config.requests.len() && !interrupt.is_interrupted() {
+        println!(
+            "\n{}",
+            format!("Step {}/{}", *current_step + 1, config.requests.len()).bold()
+        );
+        match execute_request_with_option(
+            client,
+            options,
+            session,
+            config,
+            config_path,
+            *current_step,
+            skip_confirmations,
+        )
+        .await
+        {
+            Ok(true) => {
+                summary.completed += 1;
+                *current_step += 1;
             }
-            "a" | "all" => {
-                print!(
-                    "{} [Y/n]: ",
-                    "Execute all remaining requests without confirmation?".bold()
+            // Cancelled at the confirmation prompt by Ctrl-C
+            Ok(false) if interrupt.is_interrupted() => break,
+            Ok(false) => {
+                println!("{}", "Request skipped".yellow());
+                summary.skipped += 1;
+                *current_step += 1;
+            }
+            Err(e) => {
+                summary.failed += 1;
+                println!("{} {}", "❌ Error:".red(), e);
+                println!(
+                    "{}",
+                    "Stopping execution. Use 'r' to retry this step and continue, or 'n' to step manually."
+                        .yellow()
                 );
-                io::stdout().flush()?;
+                break;
+            }
+        }
+    }
+    interrupt.set_busy(false);
 
-                let mut confirm_all = String::new();
-                io::stdin().read_line(&mut confirm_all)?;
-                let skip_confirmations = confirm_all.trim().to_lowercase() != "n"
-                    && confirm_all.trim().to_lowercase() != "no";
+    if interrupt.is_interrupted() {
+        summary.print("⏹️  Interrupted - partial summary:");
+        println!(
+            "{}",
+            format!(
+                "   Stopped before step {}. Captured variables were saved after each request.",
+                *current_step + 1
+            )
+            .dimmed()
+        );
+        exit_interrupted(session, options);
+    }
 
-                println!("{}", "Executing all remaining requests...".blue());
-                while current_step < config.requests.len() {
-                    println!(
-                        "\n{}",
-                        format!("Step {}/{}", current_step + 1, config.requests.len()).bold()
-                    );
-                    match execute_request_with_option(
-                        &client,
-                        &base_url,
-                        &default_api_key,
-                        &mut config,
-                        &args.config,
-                        current_step,
-                        skip_confirmations,
-                    )
-                    .await
-                    {
-                        Ok(true) => {
-                            current_step += 1;
-                        }
-                        Ok(false) => {
-                            println!("{}", "Request skipped".yellow());
-                            current_step += 1;
-                        }
-                        Err(e) => {
-                            println!("{} {}", "❌ Error:".red(), e);
-                            println!(
-                                "{}",
-                                "Stopping execution. Use 'n' to continue from here.".yellow()
-                            );
-                            break;
-                        }
-                    }
-                }
-                if current_step >= config.requests.len() {
-                    println!("\n{}", "✅ All requests completed!".bold().green());
-                }
+    summary.print("📊 Summary:");
+    if *current_step >= config.requests.len() {
+        println!("\n{}", "✅ All requests completed!".bold().green());
+    }
+
+    summary.failed == 0
+}
+
+/// Prompt reader with history, inline editing and tab completion. Falls back
+/// to plain line reads when stdin isn't a terminal.
+struct LineEditor {
+    editor: Option<Editor<PromptHelper, DefaultHistory>>,
+}
+
+impl Default for LineEditor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// What a prompt produced.
+enum Input {
+    Line(String),
+    /// End of input (Ctrl-D or closed stdin)
+    Eof,
+    /// Ctrl-C at the prompt
+    Interrupted,
+}
+
+impl LineEditor {
+    fn new() -> Self {
+        let editor = if io::stdin().is_terminal() {
+            Editor::new().ok().map(|mut editor: Editor<_, _>| {
+                editor.set_helper(Some(PromptHelper::default()));
+                editor
+            })
+        } else {
+            None
+        };
+        LineEditor { editor }
+    }
+
+    /// Reads one line, completing against `candidates` and `${name}`
+    /// references to `variables`.
+    fn read_line(
+        &mut self,
+        prompt: &str,
+        candidates: Vec<String>,
+        variables: &HashMap<String, String>,
+    ) -> Result<Input> {
+        let Some(editor) = &mut self.editor else {
+            print!("{}", prompt);
+            io::stdout().flush()?;
+
+            let mut line = String::new();
+            if io::stdin().read_line(&mut line)? == 0 {
+                return Ok(Input::Eof);
             }
-            _ => {
-                // Try to parse as step number
-                if let Ok(step_num) = choice.parse::<usize>() {
-                    if step_num > 0 && step_num <= config.requests.len() {
-                        let step_index = step_num - 1;
-                        match execute_request_with_option(
-                            &client,
-                            &base_url,
-                            &default_api_key,
-                            &mut config,
-                            &args.config,
-                            step_index,
-                            false,
-                        )
-                        .await
-                        {
-                            Ok(true) => {
-                                println!("{}", "✅ Request completed successfully".green());
-                                // Update current step if we executed the next one
-                                if step_index == current_step {
-                                    current_step += 1;
-                                }
-                            }
-                            Ok(false) => {
-                                println!("{}", "Request cancelled".yellow());
-                            }
-                            Err(e) => {
-                                println!("{} {}", "❌ Error:".red(), e);
-                            }
-                        }
-                    } else {
-                        println!("{}", "Invalid step number".red());
-                    }
-                } else {
-                    println!(
-                        "{}",
-                        "Invalid option. Try 'v', 's', 'l', 'n', 'a', or a step number.".red()
-                    );
+            return Ok(Input::Line(line));
+        };
+
+        if let Some(helper) = editor.helper_mut() {
+            helper.candidates = candidates;
+            helper.variables = variables.keys().cloned().collect();
+            helper.variables.sort();
+        }
+
+        match editor.readline(prompt) {
+            Ok(line) => {
+                if !line.trim().is_empty() {
+                    let _ = editor.add_history_entry(line.as_str());
                 }
+                Ok(Input::Line(line))
             }
+            Err(ReadlineError::Eof) => Ok(Input::Eof),
+            // The terminal is in raw mode, so Ctrl-C arrives here instead of as a signal
+            Err(ReadlineError::Interrupted) => Ok(Input::Interrupted),
+            Err(e) => Err(anyhow::anyhow!("Failed to read input: {}", e)),
+        }
+    }
+}
+
+#[derive(Default)]
+struct PromptHelper {
+    candidates: Vec<String>,
+    variables: Vec<String>,
+}
+
+impl Completer for PromptHelper {
+    type Candidate = String;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &rustyline::Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<String>)> {
+        let before = &line[..pos];
+
+        // Inside a `${` reference, complete variable names
+        if let Some(start) = before.rfind("${").filter(|&i| !before[i..].contains('}')) {
+            let prefix = &before[start + 2..];
+            let matches = self
+                .variables
+                .iter()
+                .filter(|name| name.starts_with(prefix))
+                .map(|name| format!("${{{}}}", name))
+                .collect();
+            return Ok((start, matches));
         }
+
+        let prefix = before.to_lowercase();
+        let matches = self
+            .candidates
+            .iter()
+            .filter(|candidate| candidate.to_lowercase().starts_with(&prefix))
+            .cloned()
+            .collect();
+        Ok((0, matches))
     }
+}
 
-    Ok(())
+impl Hinter for PromptHelper {
+    type Hint = String;
 }
 
-fn set_variable(variables: &mut HashMap<String, String>) -> Result<()> {
-    print!("{} ", "Variable name:".bold());
-    io::stdout().flush()?;
+impl Highlighter for PromptHelper {}
 
-    let mut var_name = String::new();
-    io::stdin().read_line(&mut var_name)?;
+impl Validator for PromptHelper {}
+
+impl rustyline::Helper for PromptHelper {}
+
+fn set_variable(
+    line_editor: &mut LineEditor,
+    variables: &mut HashMap<String, String>,
+    edited: &mut BTreeSet<String>,
+) -> Result<()> {
+    let mut names: Vec<String> = variables.keys().cloned().collect();
+    names.sort();
+    let Input::Line(var_name) =
+        line_editor.read_line(&format!("{} ", "Variable name:".bold()), names, variables)?
+    else {
+        // Ctrl-C or Ctrl-D cancels the set and returns to the menu
+        println!("{}", "Cancelled".yellow());
+        return Ok(());
+    };
     let var_name = var_name.trim().to_string();
 
     if var_name.is_empty() {
@@ -332,14 +1855,16 @@ fn set_variable(variables: &mut HashMap<String, String>) -> Result<()> {
         println!("Current value: {}", current_value.cyan());
     }
 
-    print!("{} ", "New value:".bold());
-    io::stdout().flush()?;
-
-    let mut var_value = String::new();
-    io::stdin().read_line(&mut var_value)?;
+    let Input::Line(var_value) =
+        line_editor.read_line(&format!("{} ", "New value:".bold()), Vec::new(), variables)?
+    else {
+        println!("{}", "Cancelled".yellow());
+        return Ok(());
+    };
     let var_value = var_value.trim().to_string();
 
     variables.insert(var_name.clone(), var_value.clone());
+    edited.insert(var_name.clone());
     println!("✅ Set {}: {}", var_name.yellow(), var_value.green());
 
     Ok(())
@@ -352,8 +1877,13 @@ fn show_menu(config: &ApilineConfig, current_step: usize) -> Result<()> {
     println!("  {} - List all requests", "l".bold().yellow());
     println!("  {} - Execute next request", "n".bold().green());
     println!("  {} - Execute all remaining", "a".bold().green());
+    println!("  {} - Retry failed step and continue", "r".bold().green());
     println!(
-        "  {} - Execute specific step (e.g., '3')",
+        "  {} - List workflows / run one with 'w <name>'",
+        "w".bold().green()
+    );
+    println!(
+        "  {} - Execute specific step (e.g., '3' or a request name)",
         "1-N".bold().blue()
     );
     println!("  {} - Quit", "q".bold().red());
@@ -382,16 +1912,40 @@ fn show_variables(variables: &HashMap<String, String>) {
         return;
     }
 
-    for (key, value) in variables {
+    let mut keys: Vec<&String> = variables.keys().collect();
+    keys.sort();
+
+    for key in keys {
+        let value = &variables[key];
+        // Variables are stored as text; JSON-looking values are shown by type
+        let parsed = serde_json::from_str::<serde_json::Value>(value).ok();
+        let type_tag = match &parsed {
+            Some(serde_json::Value::Object(_)) => "object",
+            Some(serde_json::Value::Array(_)) => "array",
+            Some(serde_json::Value::Number(_)) => "number",
+            Some(serde_json::Value::Bool(_)) => "bool",
+            _ => "string",
+        };
+        let type_tag = format!("[{}]", type_tag).dimmed();
+
+        if let Some(structured @ (serde_json::Value::Object(_) | serde_json::Value::Array(_))) =
+            &parsed
+        {
+            println!("  {} {}:", key.yellow(), type_tag);
+            let pretty = serde_json::to_string_pretty(structured).unwrap_or_default();
+            for line in pretty.lines() {
+                println!("      {}", line.cyan());
+            }
+            continue;
+        }
+
         let display_value = if value.is_empty() {
             "<empty>".dimmed().to_string()
-        } else if value.len() > 60 {
-            format!("{}...", &value[..57])
         } else {
-            value.clone()
+            truncate_chars(value, 60)
         };
 
-        println!("  {}: {}", key.yellow(), display_value.cyan());
+        println!("  {} {}: {}", key.yellow(), type_tag, display_value.cyan());
     }
 }
 
@@ -408,27 +1962,60 @@ fn list_requests(requests: &[ApiRequest], current_step: usize) {
             "⏳".dimmed()
         };
 
+        let tags = if request.tags.is_empty() {
+            String::new()
+        } else {
+            format!(" [{}]", request.tags.join(", "))
+        };
+
         println!(
-            "  {} {}: {} {} {}",
+            "  {} {}: {} {} {}{}",
             status,
             format!("{:2}", i + 1).bold(),
             request.method.to_uppercase().magenta(),
             request.endpoint.cyan(),
-            request.name.green()
+            request.name.green(),
+            tags.dimmed()
         );
     }
 }
 
+fn print_requests_json(requests: &[ApiRequest]) -> Result<()> {
+    let list: Vec<serde_json::Value> = requests
+        .iter()
+        .enumerate()
+        .map(|(i, request)| {
+            serde_json::json!({
+                "step": i + 1,
+                "name": request.name,
+                "method": request.method.to_uppercase(),
+                "endpoint": request.endpoint,
+                "tags": request.tags,
+            })
+        })
+        .collect();
+
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&list).context("Failed to serialize request list")?
+    );
+    Ok(())
+}
+
 async fn execute_request_with_option(
     client: &Client,
-    base_url: &str,
-    default_api_key: &str,
+    options: &RequestOptions,
+    session: &mut Session,
     config: &mut ApilineConfig,
     config_path: &Path,
     step_index: usize,
     skip_confirmation: bool,
 ) -> Result<bool> {
-    let request = config.requests[step_index].clone();
+    let mut request = config.requests[step_index].clone();
+    if request.auth.is_none() {
+        request.auth = config.default_auth.clone();
+    }
+    let auth = request.auth()?;
 
     println!(
         "\n{} {}: {}",
@@ -444,6 +2031,15 @@ async fn execute_request_with_option(
 
     // Substitute variables in payload
     let payload = if let Some(mut payload) = request.payload.clone() {
+        // Never send a body with placeholders nothing could resolve
+        let unresolved = unresolved_placeholders(&payload, &config.variables);
+        if !unresolved.is_empty() {
+            return Err(anyhow::anyhow!(
+                "Payload has unresolved placeholders: {}",
+                unresolved.join(", ")
+            ));
+        }
+
         substitute_variables(&mut payload, &config.variables)?;
         Some(payload)
     } else {
@@ -455,10 +2051,22 @@ async fn execute_request_with_option(
     println!("\n{}", "📋 Request Preview:".bold().yellow());
     println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
     println!("Method: {}", request.method.to_uppercase().magenta());
-    println!("URL: {}{}", base_url.cyan(), substituted_endpoint.cyan());
-    println!("Auth: {}", request.auth.blue());
+    println!(
+        "URL: {}{}",
+        options.base_url.cyan(),
+        substituted_endpoint.cyan()
+    );
+    println!("Auth: {}", auth.blue());
+    if let Some(accept) = &request.accept {
+        println!("Accept: {}", accept.blue());
+    }
 
-    if let Some(ref payload) = payload {
+    if payload
+        .as_ref()
+        .is_some_and(|payload| payload == &serde_json::json!({}))
+    {
+        println!("Payload: {}", "{} (empty object)".cyan());
+    } else if let Some(ref payload) = payload {
         println!("Payload:");
         println!(
             "{}",
@@ -477,47 +2085,161 @@ async fn execute_request_with_option(
             println!("Files:");
             for (field_name, file_path) in files {
                 let substituted_path = substitute_variables_in_string(file_path, &config.variables);
-                println!("  {}: {}", field_name.yellow(), substituted_path.cyan());
+                let resolved_path =
+                    resolve_file_path(&substituted_path, options.base_dir(&request));
+                println!(
+                    "  {}: {}",
+                    field_name.yellow(),
+                    resolved_path.display().to_string().cyan()
+                );
             }
         }
     } else {
         println!("Files: {}", "None".dimmed());
     }
 
+    if let Some(cookies) = &request.cookies {
+        if !cookies.is_empty() {
+            println!("Cookies:");
+            for (name, value) in cookies {
+                let substituted_value = substitute_variables_in_string(value, &config.variables);
+                println!("  {}: {}", name.yellow(), substituted_value.cyan());
+            }
+        }
+    }
+
     // Ask for confirmation (unless skipped)
+    let mut ignore_status = false;
     if !skip_confirmation {
-        print!("\n{} [Y/n]: ", "Execute this request?".bold());
-        io::stdout().flush()?;
-
-        let mut confirm = String::new();
-        io::stdin().read_line(&mut confirm)?;
-        let confirm = confirm.trim().to_lowercase();
+        let prompt = format!(
+            "\n{} [Y/n/i] {}: ",
+            "Execute this request?".bold(),
+            "(i = ignore expected status)".dimmed()
+        );
+        let candidates = vec!["y".to_string(), "n".to_string(), "i".to_string()];
+
+        // Without a terminal the read can't be interrupted, so an idle Ctrl-C
+        // exits right away instead of waiting for Enter
+        let busy = session.interrupt.is_busy();
+        session.interrupt.set_busy(false);
+        let input = session
+            .line_editor
+            .read_line(&prompt, candidates, &config.variables);
+        session.interrupt.set_busy(busy);
+
+        let confirm = match input? {
+            Input::Line(confirm) => confirm.trim().to_lowercase(),
+            // End of input never confirms a request
+            Input::Eof => {
+                println!("\n{}", "❌ Request cancelled".yellow());
+                return Ok(false);
+            }
+            // A Ctrl-C at the prompt cancels the request instead of sending it
+            Input::Interrupted => {
+                session.interrupt.interrupt();
+                println!("{}", "❌ Request cancelled (interrupted)".yellow());
+                return Ok(false);
+            }
+        };
 
         if confirm == "n" || confirm == "no" {
             println!("{}", "❌ Request cancelled".yellow());
             return Ok(false);
         }
+        ignore_status = confirm == "i";
     }
 
     // Make API call
     let response = make_api_call(
         client,
-        base_url,
-        default_api_key,
-        &config.variables.get("jwt_token").unwrap_or(&String::new()),
+        options,
+        session,
         &request,
-        payload,
+        payload.clone(),
         &config.variables,
-        config_path,
     )
     .await?;
 
+    if let Some(compare_base_url) = &options.compare_base_url {
+        let compare_options = RequestOptions {
+            base_url: compare_base_url.clone(),
+            ..options.clone()
+        };
+        compare_responses(
+            client,
+            &compare_options,
+            session,
+            &request,
+            payload,
+            &config.variables,
+            &response,
+        )
+        .await;
+    }
+
+    let status = response.status;
+    let headers = response.headers.clone();
+    let response = if status.as_u16() == request.expected_status {
+        response.body(request.expects_json())?
+    } else if ignore_status {
+        println!(
+            "   {}",
+            format!(
+                "ℹ️  Expected status {}, got {} (ignored for this run)",
+                request.expected_status, status
+            )
+            .yellow()
+        );
+        // Captures still run against whatever came back
+        response
+            .body(request.expects_json())
+            .unwrap_or(serde_json::Value::String(response.text))
+    } else {
+        return Err(anyhow::anyhow!(
+            "Expected status {}, got {}: {}",
+            request.expected_status,
+            status,
+            response.text
+        ));
+    };
+
+    if request.strict {
+        // `load_config` rejects `strict` without `expected_fields`
+        let expected_fields = request.expected_fields.as_deref().unwrap_or_default();
+        // Only an object has fields to check; anything else breaks the contract
+        let serde_json::Value::Object(map) = &response else {
+            return Err(anyhow::anyhow!(
+                "Strict check expects a JSON object response, got: {}",
+                json_value_to_string(&response)
+            ));
+        };
+        let mut unexpected: Vec<&str> = map
+            .keys()
+            .filter(|key| !expected_fields.contains(key))
+            .map(String::as_str)
+            .collect();
+        if !unexpected.is_empty() {
+            unexpected.sort();
+            return Err(anyhow::anyhow!(
+                "Response has unexpected fields: {}",
+                unexpected.join(", ")
+            ));
+        }
+    }
+
     // Save response values
     let mut variables_updated = false;
 
-    if let (Some(save_as), Some(extract_path)) = (&request.save_as, &request.extract_path) {
-        if let Some(value) = extract_json_path(&response, extract_path)? {
+    if let Some(save_as) = &request.save_as {
+        // Without an extract_path the whole response body is saved
+        let value = match &request.extract_path {
+            Some(extract_path) => extract_json_path(&response, extract_path)?,
+            None => Some(json_value_to_string(&response)),
+        };
+
+        if let Some(value) = value {
             config.variables.insert(save_as.clone(), value.clone());
+            session.captured.insert(save_as.clone());
             println!("   💾 Saved {}: {}", save_as.yellow(), value.green());
             variables_updated = true;
         }
@@ -527,24 +2249,47 @@ async fn execute_request_with_option(
         for (var_name, extract_path) in save_multiple {
             if let Some(value) = extract_json_path(&response, extract_path)? {
                 config.variables.insert(var_name.clone(), value.clone());
+                session.captured.insert(var_name.clone());
                 println!("   💾 Saved {}: {}", var_name.yellow(), value.green());
                 variables_updated = true;
             }
         }
     }
 
+    if let Some(save_cookies) = &request.save_cookies {
+        for (var_name, cookie_name) in save_cookies {
+            match find_set_cookie(&headers, cookie_name) {
+                Some(value) => {
+                    config.variables.insert(var_name.clone(), value.clone());
+                    session.captured.insert(var_name.clone());
+                    println!("   🍪 Saved {}: {}", var_name.yellow(), value.green());
+                    variables_updated = true;
+                }
+                None => {
+                    session.warn(format!(
+                        "{}: cookie '{}' was not set by the response",
+                        request.name, cookie_name
+                    ));
+                }
+            }
+        }
+    }
+
     // Persist variables to config file
     if variables_updated {
-        match save_config(config_path, config) {
+        let changed = session.captured.union(&session.edited).cloned().collect();
+        match save_variables(config_path, &config.variables, &changed) {
             Ok(()) => {
                 println!("{}", "   📝 Variables saved to config file".dimmed());
             }
             Err(e) => {
-                println!(
-                    "   {}  {}",
-                    "⚠️  Warning: Failed to save config:".yellow(),
-                    e
-                );
+                session.warn(format!("Failed to save config: {}", e));
+            }
+        }
+
+        if let Some(export_path) = &options.export_env {
+            if let Err(e) = export_env_file(export_path, &session.captured, &config.variables) {
+                session.warn(format!("Failed to export variables: {}", e));
             }
         }
     }
@@ -552,18 +2297,73 @@ async fn execute_request_with_option(
     Ok(true)
 }
 
+/// Names that look like credentials are kept out of exports and dumps.
+fn is_secret_variable(name: &str) -> bool {
+    let name = name.to_lowercase();
+    [
+        "token",
+        "password",
+        "secret",
+        "api_key",
+        "apikey",
+        "credential",
+    ]
+    .iter()
+    .any(|marker| name.contains(marker))
+}
+
+/// Writes captured variables as `export NAME='value'` lines that a shell can
+/// `source`. Secrets and names that aren't valid shell identifiers are skipped.
+fn export_env_file(
+    export_path: &Path,
+    captured: &BTreeSet<String>,
+    variables: &HashMap<String, String>,
+) -> Result<()> {
+    let mut content = String::new();
+
+    for name in captured {
+        let Some(value) = variables.get(name) else {
+            continue;
+        };
+        let valid_name = name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+            && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+        if is_secret_variable(name) || !valid_name {
+            continue;
+        }
+
+        content.push_str(&format!(
+            "export {}='{}'\n",
+            name,
+            value.replace('\'', "'\\''")
+        ));
+    }
+
+    std::fs::write(export_path, content)
+        .with_context(|| format!("Failed to write env file {:?}", export_path))
+}
+
 fn substitute_variables(
     value: &mut serde_json::Value,
     variables: &HashMap<String, String>,
 ) -> Result<()> {
     match value {
         serde_json::Value::String(s) => {
-            for (var_name, var_value) in variables {
-                let placeholder = format!("${{{}}}", var_name);
-                if s.contains(&placeholder) {
-                    *s = s.replace(&placeholder, var_value);
+            // A string that is exactly one placeholder for a captured object or
+            // array is replaced by that JSON value rather than its text
+            if let Some(var_value) = s
+                .strip_prefix("${")
+                .and_then(|rest| rest.strip_suffix('}'))
+                .and_then(|inner| variables.get(split_placeholder(inner).0))
+            {
+                if let Ok(parsed @ (serde_json::Value::Object(_) | serde_json::Value::Array(_))) =
+                    serde_json::from_str(var_value)
+                {
+                    *value = parsed;
+                    return Ok(());
                 }
             }
+
+            *s = substitute_variables_in_string(s, variables);
         }
         serde_json::Value::Object(map) => {
             for (_, v) in map.iter_mut() {
@@ -580,27 +2380,69 @@ fn substitute_variables(
     Ok(())
 }
 
+/// Replaces `${name}` and `${name:-default}` placeholders. The default is
+/// used when the variable is unset or empty; placeholders for unknown
+/// variables without a default are left as they are.
 fn substitute_variables_in_string(s: &str, variables: &HashMap<String, String>) -> String {
-    let mut result = s.to_string();
-    for (var_name, var_value) in variables {
-        let placeholder = format!("${{{}}}", var_name);
-        result = result.replace(&placeholder, var_value);
+    let mut result = String::with_capacity(s.len());
+    let mut rest = s;
+
+    while let Some(start) = rest.find("${") {
+        result.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let Some(end) = after.find('}') else {
+            rest = &rest[start..];
+            break;
+        };
+
+        let placeholder = &rest[start..start + 2 + end + 1];
+        let (name, default) = split_placeholder(&after[..end]);
+        match (variables.get(name), default) {
+            (Some(value), Some(default)) if value.is_empty() => result.push_str(default),
+            (Some(value), _) => result.push_str(value),
+            (None, Some(default)) => result.push_str(default),
+            (None, None) => result.push_str(placeholder),
+        }
+        rest = &after[end + 1..];
     }
+
+    result.push_str(rest);
     result
 }
 
+struct ApiResponse {
+    status: reqwest::StatusCode,
+    headers: HeaderMap,
+    text: String,
+}
+
+impl ApiResponse {
+    /// The body as JSON, or as a JSON string when `as_json` is false.
+    fn body(&self, as_json: bool) -> Result<serde_json::Value> {
+        if !as_json {
+            return Ok(serde_json::Value::String(self.text.clone()));
+        }
+
+        // Handle empty responses
+        if self.text.trim().is_empty() {
+            return Ok(serde_json::Value::Object(serde_json::Map::new()));
+        }
+
+        serde_json::from_str(&self.text)
+            .with_context(|| format!("Failed to parse JSON response: {}", self.text))
+    }
+}
+
 async fn make_api_call(
     client: &Client,
-    base_url: &str,
-    default_api_key: &str,
-    jwt_token: &str,
+    options: &RequestOptions,
+    session: &mut Session,
     request: &ApiRequest,
     payload: Option<serde_json::Value>,
     variables: &HashMap<String, String>,
-    config_path: &Path,
-) -> Result<serde_json::Value> {
+) -> Result<ApiResponse> {
     let endpoint = substitute_variables_in_string(&request.endpoint, variables);
-    let url = format!("{}{}", base_url, endpoint);
+    let url = format!("{}{}", options.base_url, endpoint);
 
     let method = match request.method.to_uppercase().as_str() {
         "GET" => reqwest::Method::GET,
@@ -611,48 +2453,158 @@ async fn make_api_call(
         _ => return Err(anyhow::anyhow!("Unsupported method: {}", request.method)),
     };
 
+    let span = tracing::info_span!(
+        "request",
+        request = %request.name,
+        method = %method,
+        url = %url,
+        status = tracing::field::Empty,
+        elapsed_ms = tracing::field::Empty,
+    );
+    let started = Instant::now();
+
     let mut req = client.request(method, &url);
 
-    match request.auth.as_str() {
-        "admin" => {
-            req = req.header("api-key", default_api_key);
-        }
-        "jwt" => {
-            req = req.header("Authorization", format!("Bearer {}", jwt_token));
-        }
-        "none" => {
-            // No authentication
-        }
-        custom_auth if custom_auth.starts_with("Bearer ") => {
-            req = req.header("Authorization", custom_auth);
-        }
-        custom_auth if custom_auth.starts_with("api-key:") => {
-            req = req.header("api-key", custom_auth.strip_prefix("api-key:").unwrap());
+    let auth = match request.auth()? {
+        "inherit" => session.last_auth.clone().ok_or_else(|| {
+            anyhow::anyhow!(
+                "Request '{}' uses 'auth: inherit' but no request has been executed yet",
+                request.name
+            )
+        })?,
+        auth => resolve_auth(auth, options, variables)?,
+    };
+    if let ResolvedAuth::Header(name, value) = &auth {
+        req = req.header(*name, value);
+    }
+    session.last_auth = Some(auth);
+
+    req = req.header("Accept", request.accept());
+
+    if let Some(cookies) = &request.cookies {
+        if !cookies.is_empty() {
+            let mut pairs: Vec<String> = cookies
+                .iter()
+                .map(|(name, value)| {
+                    format!(
+                        "{}={}",
+                        name,
+                        substitute_variables_in_string(value, variables)
+                    )
+                })
+                .collect();
+            pairs.sort();
+            req = req.header("Cookie", pairs.join("; "));
         }
-        _ => return Err(anyhow::anyhow!("Unknown auth type: {}", request.auth)),
     }
 
-    if let Some(files) = &request.files {
-        if !files.is_empty() {
-            let form = build_multipart_form(files, payload, variables, config_path).await?;
-            req = req.multipart(form);
-        } else if let Some(payload) = payload {
-            req = req
-                .header("Content-Type", "application/json")
-                .json(&payload);
-        }
-    } else if let Some(payload) = payload {
-        req = req
-            .header("Content-Type", "application/json")
-            .json(&payload);
+    let cached = if request.cache_etag {
+        session.etag_cache.get(&url)
     } else {
-        req = req.header("Content-Type", "application/json");
+        None
+    };
+    if let Some(cached) = cached {
+        req = req.header("If-None-Match", &cached.etag);
     }
 
-    let response = req.send().await.context("Failed to send request")?;
+    let retry = request.retry.clone().unwrap_or_default();
+    let mut attempt = 1;
+    let mut waited = Duration::ZERO;
+
+    let (status, headers, response_text) = loop {
+        // A builder without a body can always be cloned
+        let attempt_req = req
+            .try_clone()
+            .context("Failed to prepare request for sending")?;
+        let attempt_req = attach_body(
+            attempt_req,
+            request,
+            options,
+            payload.as_ref(),
+            variables,
+            options.base_dir(request),
+        )
+        .await?;
+
+        let (result, retry_reason) = match attempt_req.send().await {
+            Ok(response) => {
+                let status = response.status();
+                let headers = response.headers().clone();
+                let text = read_body_limited(response, options.max_response_bytes).await?;
+                let reason = if retry.retries_status(status.as_u16()) {
+                    Some(status.to_string())
+                } else {
+                    retry.matching_body_condition(&text)
+                };
+                (Ok((status, headers, text)), reason)
+            }
+            Err(e) => {
+                let reason = e.to_string();
+                (Err(e), Some(reason))
+            }
+        };
+
+        if let Some(reason) = retry_reason {
+            if let Some(delay) = retry.next_delay(attempt, waited) {
+                span.in_scope(|| {
+                    tracing::warn!(
+                        attempt,
+                        delay_ms = delay.as_millis() as u64,
+                        %reason,
+                        "retrying request"
+                    )
+                });
+                println!(
+                    "   {}",
+                    format!(
+                        "⏳ Attempt {}/{} failed ({}), retrying in {}ms",
+                        attempt,
+                        retry.max_attempts,
+                        reason,
+                        delay.as_millis()
+                    )
+                    .yellow()
+                );
+                tokio::time::sleep(delay).await;
+                waited += delay;
+                attempt += 1;
+                continue;
+            }
+
+            // A body condition still holding means the call never really succeeded
+            if let Ok((status, _, _)) = &result {
+                if !retry.retries_status(status.as_u16()) {
+                    return Err(anyhow::anyhow!(
+                        "Giving up after {} attempt(s): {}",
+                        attempt,
+                        reason
+                    ));
+                }
+            }
+        }
 
-    let status = response.status();
-    let response_text = response.text().await.context("Failed to read response")?;
+        break result.context("Failed to send request")?;
+    };
+
+    let elapsed_ms = started.elapsed().as_millis() as u64;
+    span.record("status", status.as_u16());
+    span.record("elapsed_ms", elapsed_ms);
+    span.in_scope(|| tracing::info!(bytes = response_text.len(), "response received"));
+
+    if status == reqwest::StatusCode::NOT_MODIFIED {
+        if let Some(cached) = cached {
+            println!(
+                "   📥 Response: {} {}",
+                status.as_u16().to_string().green(),
+                "Not Modified, reusing cached body".dimmed()
+            );
+            return Ok(ApiResponse {
+                status: cached.status,
+                headers,
+                text: cached.text.clone(),
+            });
+        }
+    }
 
     println!(
         "   📥 Response: {} {}",
@@ -660,37 +2612,283 @@ async fn make_api_call(
             .as_u16()
             .to_string()
             .if_else(status.is_success(), |s| s.green(), |s| s.red()),
-        if response_text.len() > 100 {
-            format!("{}...", &response_text[..97])
-        } else {
-            response_text.clone()
+        truncate_chars(&response_text, 100).dimmed()
+    );
+
+    if request.cache_etag && status.is_success() {
+        let etag = headers
+            .get(reqwest::header::ETAG)
+            .and_then(|value| value.to_str().ok());
+        if let Some(etag) = etag {
+            session.etag_cache.insert(
+                url,
+                CachedResponse {
+                    etag: etag.to_string(),
+                    status,
+                    text: response_text.clone(),
+                },
+            );
         }
-        .dimmed()
+    }
+
+    Ok(ApiResponse {
+        status,
+        headers,
+        text: response_text,
+    })
+}
+
+/// Sends the request again using `compare_options` (pointing at the
+/// `--compare` base URL) and prints a structured diff against `primary`.
+/// A failing compare request is recorded as a warning and a difference.
+async fn compare_responses(
+    client: &Client,
+    compare_options: &RequestOptions,
+    session: &mut Session,
+    request: &ApiRequest,
+    payload: Option<serde_json::Value>,
+    variables: &HashMap<String, String>,
+    primary: &ApiResponse,
+) {
+    println!(
+        "   {}",
+        format!("🔀 Comparing with {}", compare_options.base_url).dimmed()
     );
+    let other = make_api_call(
+        client,
+        compare_options,
+        session,
+        request,
+        payload,
+        variables,
+    )
+    .await;
+    // The primary request already ran, so a failing compare side must not
+    // abort the step and lose its captures
+    let other = match other {
+        Ok(other) => other,
+        Err(e) => {
+            let reason = match e.chain().count() {
+                1 => e.to_string(),
+                _ => format!("{} ({})", e, e.root_cause()),
+            };
+            session.warn(format!(
+                "{}: compare request failed: {}",
+                request.name, reason
+            ));
+            session.differing.push(request.name.clone());
+            return;
+        }
+    };
 
-    if status.as_u16() != request.expected_status {
-        return Err(anyhow::anyhow!(
-            "Expected status {}, got {}: {}",
-            request.expected_status,
-            status,
-            response_text
+    let mut differences = Vec::new();
+    if primary.status != other.status {
+        differences.push(format!(
+            "~ status: {} → {}",
+            primary.status.as_u16(),
+            other.status.as_u16()
         ));
     }
+    let body = |response: &ApiResponse| {
+        response
+            .body(request.expects_json())
+            .unwrap_or_else(|_| serde_json::Value::String(response.text.clone()))
+    };
+    diff_json(
+        "$",
+        &body(primary),
+        &body(&other),
+        &compare_options.ignore_fields,
+        &mut differences,
+    );
+
+    if differences.is_empty() {
+        println!("   {}", "✅ Responses match".green());
+        return;
+    }
+
+    println!(
+        "   {}",
+        format!("❗ {} difference(s):", differences.len())
+            .bold()
+            .yellow()
+    );
+    for difference in &differences {
+        let line = match difference.chars().next() {
+            Some('-') => difference.red(),
+            Some('+') => difference.green(),
+            _ => difference.yellow(),
+        };
+        println!("      {}", line);
+    }
+    session.differing.push(request.name.clone());
+}
+
+/// Appends one line per difference between `left` and `right`: `~` for changed
+/// values, `-` for fields only on the left and `+` for fields only on the right.
+/// Fields matching an ignored name or `$.path` are skipped.
+fn diff_json(
+    path: &str,
+    left: &serde_json::Value,
+    right: &serde_json::Value,
+    ignore_fields: &[String],
+    differences: &mut Vec<String>,
+) {
+    use serde_json::Value;
+
+    match (left, right) {
+        (Value::Object(left), Value::Object(right)) => {
+            let mut keys: BTreeSet<&String> = left.keys().collect();
+            keys.extend(right.keys());
+            for key in keys {
+                let child = format!("{}.{}", path, key);
+                if ignore_fields
+                    .iter()
+                    .any(|field| field == key || *field == child)
+                {
+                    continue;
+                }
+                match (left.get(key), right.get(key)) {
+                    (Some(l), Some(r)) => diff_json(&child, l, r, ignore_fields, differences),
+                    (Some(l), None) => differences.push(format!("- {}: {}", child, l)),
+                    (None, Some(r)) => differences.push(format!("+ {}: {}", child, r)),
+                    (None, None) => {}
+                }
+            }
+        }
+        (Value::Array(left), Value::Array(right)) => {
+            for i in 0..left.len().max(right.len()) {
+                let child = format!("{}[{}]", path, i);
+                match (left.get(i), right.get(i)) {
+                    (Some(l), Some(r)) => diff_json(&child, l, r, ignore_fields, differences),
+                    (Some(l), None) => differences.push(format!("- {}: {}", child, l)),
+                    (None, Some(r)) => differences.push(format!("+ {}: {}", child, r)),
+                    (None, None) => {}
+                }
+            }
+        }
+        _ if left != right => differences.push(format!("~ {}: {} → {}", path, left, right)),
+        _ => {}
+    }
+}
+
+/// Reads the body chunk by chunk, failing as soon as it grows past `limit`
+/// instead of buffering an arbitrarily large response.
+async fn read_body_limited(mut response: reqwest::Response, limit: usize) -> Result<String> {
+    let too_large = || {
+        anyhow::anyhow!(
+            "Response body exceeds {} bytes (raise it with --max-response-bytes)",
+            limit
+        )
+    };
+
+    if response
+        .content_length()
+        .is_some_and(|length| length > limit as u64)
+    {
+        return Err(too_large());
+    }
+
+    let mut body = Vec::new();
+    while let Some(chunk) = response.chunk().await.context("Failed to read response")? {
+        if body.len() + chunk.len() > limit {
+            return Err(too_large());
+        }
+        body.extend_from_slice(&chunk);
+    }
+
+    Ok(String::from_utf8_lossy(&body).into_owned())
+}
+
+/// Value of the named cookie from the response's `Set-Cookie` headers.
+fn find_set_cookie(headers: &HeaderMap, name: &str) -> Option<String> {
+    headers
+        .get_all(reqwest::header::SET_COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .filter_map(|cookie| cookie.split(';').next()?.split_once('='))
+        .find(|(cookie_name, _)| cookie_name.trim() == name)
+        .map(|(_, value)| value.trim().trim_matches('"').to_string())
+}
+
+async fn attach_body(
+    req: RequestBuilder,
+    request: &ApiRequest,
+    options: &RequestOptions,
+    payload: Option<&serde_json::Value>,
+    variables: &HashMap<String, String>,
+    base_dir: &Path,
+) -> Result<RequestBuilder> {
+    let content_type = options.content_type(request);
+    let gzip = request.gzip.unwrap_or(options.gzip_requests);
+
+    if let Some(files) = request.files.as_ref().filter(|files| !files.is_empty()) {
+        let form = build_multipart_form(files, payload.cloned(), variables, base_dir).await?;
+        return Ok(req.multipart(form));
+    }
+
+    match payload {
+        // `payload: {}` is an explicit empty-object body and is sent as `{}`
+        Some(payload) => with_json_body(req, payload, content_type, gzip),
+        // An absent payload means no body; state the zero length for methods
+        // that normally carry one so servers don't reject the request
+        None if ["POST", "PUT", "PATCH"].contains(&request.method.to_uppercase().as_str()) => {
+            Ok(req
+                .header("Content-Type", content_type)
+                .header("Content-Length", "0"))
+        }
+        None => Ok(req.header("Content-Type", content_type)),
+    }
+}
+
+fn resolve_auth(
+    auth: &str,
+    options: &RequestOptions,
+    variables: &HashMap<String, String>,
+) -> Result<ResolvedAuth> {
+    let resolved = match auth {
+        "admin" => ResolvedAuth::Header("api-key", options.default_api_key.clone()),
+        "jwt" => {
+            let jwt_token = variables.get("jwt_token").map(String::as_str).unwrap_or("");
+            ResolvedAuth::Header("Authorization", format!("Bearer {}", jwt_token))
+        }
+        "none" => ResolvedAuth::None,
+        custom_auth if custom_auth.starts_with("Bearer ") => {
+            ResolvedAuth::Header("Authorization", custom_auth.to_string())
+        }
+        custom_auth if custom_auth.starts_with("api-key:") => ResolvedAuth::Header(
+            "api-key",
+            custom_auth.strip_prefix("api-key:").unwrap().to_string(),
+        ),
+        _ => return Err(anyhow::anyhow!("Unknown auth type: {}", auth)),
+    };
+    Ok(resolved)
+}
 
-    // Handle empty responses
-    if response_text.trim().is_empty() {
-        return Ok(serde_json::Value::Object(serde_json::Map::new()));
+fn with_json_body(
+    req: RequestBuilder,
+    payload: &serde_json::Value,
+    content_type: &str,
+    gzip: bool,
+) -> Result<RequestBuilder> {
+    let req = req.header("Content-Type", content_type);
+    if !gzip {
+        return Ok(req.json(payload));
     }
 
-    serde_json::from_str(&response_text)
-        .with_context(|| format!("Failed to parse JSON response: {}", response_text))
+    let body = serde_json::to_vec(payload).context("Failed to serialize payload")?;
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(&body).context("Failed to gzip payload")?;
+    let compressed = encoder.finish().context("Failed to gzip payload")?;
+
+    Ok(req.header("Content-Encoding", "gzip").body(compressed))
 }
 
 async fn build_multipart_form(
     files: &HashMap<String, String>,
     payload: Option<serde_json::Value>,
     variables: &HashMap<String, String>,
-    config_path: &Path,
+    base_dir: &Path,
 ) -> Result<multipart::Form> {
     let mut form = multipart::Form::new();
 
@@ -707,7 +2905,7 @@ async fn build_multipart_form(
 
     for (field_name, file_path) in files {
         let substituted_path = substitute_variables_in_string(file_path, variables);
-        let resolved_path = resolve_file_path(&substituted_path, config_path);
+        let resolved_path = resolve_file_path(&substituted_path, base_dir);
         let file_data = tokio::fs::read(&resolved_path).await.with_context(|| {
             format!("Failed to read file for field '{field_name}': {resolved_path:?}")
         })?;
@@ -726,29 +2924,151 @@ async fn build_multipart_form(
     Ok(form)
 }
 
-fn resolve_file_path(file_path: &str, config_path: &Path) -> PathBuf {
+fn resolve_file_path(file_path: &str, base_dir: &Path) -> PathBuf {
     let path = PathBuf::from(file_path);
 
-    if path.is_absolute() || path.exists() {
+    if path.is_absolute() {
         path
     } else {
-        config_path
-            .parent()
-            .unwrap_or_else(|| Path::new("."))
-            .join(path)
+        base_dir.join(path)
     }
 }
 
+/// Extracts a field as a string. The path may be followed by `|`-separated
+/// transforms, e.g. `$.email | trim | lowercase` or `$.name | split(' ')[0]`.
 fn extract_json_path(response: &serde_json::Value, path: &str) -> Result<Option<String>> {
-    if path.starts_with("$.") {
-        let field = &path[2..];
-        if let Some(value) = response.get(field) {
-            return Ok(Some(
-                value.as_str().unwrap_or(&value.to_string()).to_string(),
-            ));
+    let mut segments = split_transforms(path).into_iter();
+    let path = segments.next().unwrap_or_default();
+
+    let Some(field) = path.strip_prefix("$.") else {
+        return Ok(None);
+    };
+    let Some(value) = response.get(field) else {
+        return Ok(None);
+    };
+
+    let mut value = json_value_to_string(value);
+    for transform in segments {
+        value = parse_transform(transform)
+            .and_then(|parsed| apply_transform(&value, &parsed))
+            .map_err(|e| anyhow::anyhow!("Failed to apply '{}' to {}: {}", transform, path, e))?;
+    }
+    Ok(Some(value))
+}
+
+/// Checks every `|` transform of an extract path without a response, so
+/// typos are caught before any request is sent.
+fn validate_extract_path(path: &str) -> Result<()> {
+    for transform in split_transforms(path).into_iter().skip(1) {
+        parse_transform(transform)?;
+    }
+    Ok(())
+}
+
+/// Resolves `$.field` or nested `$.a.b` paths for retry `when_body` conditions.
+fn lookup_json_path<'a>(value: &'a serde_json::Value, path: &str) -> Option<&'a serde_json::Value> {
+    path.trim()
+        .strip_prefix("$.")?
+        .split('.')
+        .try_fold(value, |value, field| value.get(field))
+}
+
+/// Splits on `|` outside of quotes, trimming each part.
+fn split_transforms(path: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut quote = None;
+    let mut start = 0;
+
+    for (i, c) in path.char_indices() {
+        match (quote, c) {
+            (None, '\'' | '"') => quote = Some(c),
+            (Some(q), c) if q == c => quote = None,
+            (None, '|') => {
+                parts.push(path[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
         }
     }
-    Ok(None)
+    parts.push(path[start..].trim());
+
+    parts
+}
+
+enum Transform {
+    Trim,
+    Lowercase,
+    Uppercase,
+    Split { delimiter: String, index: usize },
+}
+
+fn parse_transform(transform: &str) -> Result<Transform> {
+    match transform {
+        "trim" => Ok(Transform::Trim),
+        "lowercase" => Ok(Transform::Lowercase),
+        "uppercase" => Ok(Transform::Uppercase),
+        _ => parse_split(transform)
+            .map(|(delimiter, index)| Transform::Split { delimiter, index })
+            .ok_or_else(|| anyhow::anyhow!("Unknown transform: {}", transform)),
+    }
+}
+
+fn apply_transform(value: &str, transform: &Transform) -> Result<String> {
+    match transform {
+        Transform::Trim => Ok(value.trim().to_string()),
+        Transform::Lowercase => Ok(value.to_lowercase()),
+        Transform::Uppercase => Ok(value.to_uppercase()),
+        Transform::Split { delimiter, index } => value
+            .split(delimiter.as_str())
+            .nth(*index)
+            .map(str::to_string)
+            .ok_or_else(|| {
+                anyhow::anyhow!(
+                    "'{}' has no part {} when split on '{}'",
+                    value,
+                    index,
+                    delimiter
+                )
+            }),
+    }
+}
+
+/// Parses `split('<delimiter>')[<index>]`.
+fn parse_split(transform: &str) -> Option<(String, usize)> {
+    let args = transform.strip_prefix("split(")?;
+    let quote = args.chars().next().filter(|c| *c == '\'' || *c == '"')?;
+    let args = &args[1..];
+    let end = args.find(quote)?;
+    let delimiter = &args[..end];
+    let index = args[end + 1..]
+        .strip_prefix(")[")?
+        .strip_suffix(']')?
+        .trim()
+        .parse()
+        .ok()?;
+
+    if delimiter.is_empty() {
+        return None;
+    }
+    Some((delimiter.to_string(), index))
+}
+
+/// Shortens `value` to at most `max` characters, ending in `...` when cut.
+/// Counts characters rather than bytes so multi-byte text is never split.
+fn truncate_chars(value: &str, max: usize) -> String {
+    if value.chars().count() <= max {
+        return value.to_string();
+    }
+    let kept: String = value.chars().take(max.saturating_sub(3)).collect();
+    format!("{}...", kept)
+}
+
+/// Strings are stored as-is, everything else as compact JSON.
+fn json_value_to_string(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
 }
 
 trait ColoredExt {
@@ -785,3 +3105,198 @@ impl ColoredExt for String {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::{HeaderValue, SET_COOKIE};
+    use serde_json::json;
+
+    fn vars(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn saving_variables_keeps_values_overridden_by_later_files() {
+        let dir = std::env::temp_dir().join(format!("apiline-save-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let first = dir.join("a.yaml");
+        std::fs::write(
+            &first,
+            "variables:\n  env: dev\nrequests:\n  - name: login\n    method: GET\n    endpoint: /login\n",
+        )
+        .unwrap();
+
+        // Merged view of a.yaml + b.yaml, where b.yaml declares env and b_only
+        let variables = vars(&[("env", "prod"), ("b_only", "2"), ("token", "abc")]);
+        let changed = BTreeSet::from(["token".to_string()]);
+        save_variables(&first, &variables, &changed).unwrap();
+
+        let saved = load_config(&first).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(saved.variables, vars(&[("env", "dev"), ("token", "abc")]));
+        assert_eq!(saved.requests.len(), 1);
+    }
+
+    #[test]
+    fn long_values_are_truncated_by_characters() {
+        let value = "é".repeat(40);
+        assert_eq!(truncate_chars(&value, 60), value);
+        let value = "é".repeat(61);
+        assert_eq!(truncate_chars(&value, 60), format!("{}...", "é".repeat(57)));
+    }
+
+    #[test]
+    fn default_used_when_variable_unset_or_empty() {
+        assert_eq!(substitute_variables_in_string("${a:-d}", &vars(&[])), "d");
+        assert_eq!(
+            substitute_variables_in_string("${a:-d}", &vars(&[("a", "")])),
+            "d"
+        );
+        assert_eq!(
+            substitute_variables_in_string("/p/${a:-d}?x=${a}", &vars(&[("a", "v")])),
+            "/p/v?x=v"
+        );
+    }
+
+    #[test]
+    fn unknown_and_unterminated_placeholders_are_left_untouched() {
+        let variables = vars(&[("a", "1")]);
+        assert_eq!(
+            substitute_variables_in_string("${b}/${a}", &variables),
+            "${b}/1"
+        );
+        assert_eq!(
+            substitute_variables_in_string("${a}/${a", &variables),
+            "1/${a"
+        );
+    }
+
+    #[test]
+    fn substituted_values_are_not_expanded_again() {
+        let variables = vars(&[("a", "${b}"), ("b", "2")]);
+        assert_eq!(substitute_variables_in_string("${a}", &variables), "${b}");
+    }
+
+    #[test]
+    fn placeholders_with_defaults_are_not_reported() {
+        assert_eq!(split_placeholder("a:-b:-c"), ("a", Some("b:-c")));
+        assert_eq!(split_placeholder("a"), ("a", None));
+        assert_eq!(find_placeholders("${a:-1}/${b}"), vec!["b"]);
+    }
+
+    #[test]
+    fn whole_value_object_placeholder_honours_default() {
+        let mut payload = json!({"user": "${user:-none}", "other": "${other:-none}"});
+        let variables = vars(&[("user", r#"{"id": 7}"#)]);
+        substitute_variables(&mut payload, &variables).unwrap();
+        assert_eq!(payload, json!({"user": {"id": 7}, "other": "none"}));
+    }
+
+    #[test]
+    fn placeholders_are_found_in_order() {
+        assert_eq!(
+            find_placeholders("/users/${id}/posts/${post}?x=${id"),
+            vec!["id", "post"]
+        );
+        let mut names = Vec::new();
+        let payload = serde_json::json!({"a": "${one}", "b": ["${two}", 3]});
+        collect_value_placeholders(&payload, &mut names);
+        names.sort();
+        assert_eq!(names, vec!["one", "two"]);
+    }
+
+    #[test]
+    fn extract_path_applies_split_transform() {
+        let response = json!({"header": "Bearer abc|def"});
+        assert_eq!(
+            extract_json_path(&response, "$.header | split(' ')[1]").unwrap(),
+            Some("abc|def".to_string())
+        );
+        assert_eq!(
+            extract_json_path(&response, "$.header | split('|')[1] | uppercase").unwrap(),
+            Some("DEF".to_string())
+        );
+        assert!(extract_json_path(&response, "$.header | split('|')[5]").is_err());
+        assert_eq!(
+            split_transforms("$.a | split('|')[1] | trim"),
+            vec!["$.a", "split('|')[1]", "trim"]
+        );
+        assert_eq!(parse_split("split(\",\")[2]"), Some((",".to_string(), 2)));
+        assert_eq!(parse_split("split(,)[2]"), None);
+        assert!(validate_extract_path("$.email | trim | split('@')[0]").is_ok());
+        assert!(validate_extract_path("$.email | lowercse").is_err());
+        assert!(validate_extract_path("$.email | split(,)[0]").is_err());
+    }
+
+    #[test]
+    fn dependency_cycles_are_found() {
+        assert_eq!(find_dependency_cycle(&[vec![0]]), Some(vec![0, 0]));
+        assert_eq!(
+            find_dependency_cycle(&[vec![], vec![2], vec![1]]),
+            Some(vec![1, 2, 1])
+        );
+        assert_eq!(find_dependency_cycle(&[vec![], vec![0], vec![0, 1]]), None);
+    }
+
+    #[test]
+    fn extract_path_reads_top_level_keys_with_dots() {
+        let response = json!({"a.b": "flat", "a": {"b": "nested"}});
+        assert_eq!(
+            extract_json_path(&response, "$.a.b").unwrap(),
+            Some("flat".to_string())
+        );
+    }
+
+    #[test]
+    fn set_cookie_values_are_found_by_name() {
+        let mut headers = HeaderMap::new();
+        headers.append(SET_COOKIE, HeaderValue::from_static("theme=dark"));
+        headers.append(
+            SET_COOKIE,
+            HeaderValue::from_static("session_id=\"abc=1\"; Path=/; HttpOnly"),
+        );
+        assert_eq!(
+            find_set_cookie(&headers, "session_id"),
+            Some("abc=1".to_string())
+        );
+        assert_eq!(find_set_cookie(&headers, "theme"), Some("dark".to_string()));
+        assert_eq!(find_set_cookie(&headers, "Path"), None);
+    }
+
+    #[test]
+    fn diff_skips_ignored_names_and_paths() {
+        let left = json!({"id": 1, "meta": {"at": "t1", "v": 1}, "items": [1, 2]});
+        let right = json!({"id": 2, "meta": {"at": "t2", "v": 2}, "items": [1], "new": true});
+
+        let mut differences = Vec::new();
+        diff_json("$", &left, &right, &[], &mut differences);
+        assert_eq!(
+            differences,
+            vec![
+                "~ $.id: 1 → 2",
+                "- $.items[1]: 2",
+                "~ $.meta.at: \"t1\" → \"t2\"",
+                "~ $.meta.v: 1 → 2",
+                "+ $.new: true",
+            ]
+        );
+
+        let ignore = vec!["id".to_string(), "$.meta.at".to_string(), "new".to_string()];
+        let mut differences = Vec::new();
+        diff_json("$", &left, &right, &ignore, &mut differences);
+        assert_eq!(differences, vec!["- $.items[1]: 2", "~ $.meta.v: 1 → 2"]);
+    }
+    #[test]
+    fn unresolved_payload_placeholders_ignore_substituted_values() {
+        let variables = vars(&[("tpl", "Hello ${name}")]);
+        let payload = json!({"msg": "${tpl}", "a": "${nope}", "b": ["${nope}", "${x:-1}"]});
+        assert_eq!(
+            unresolved_placeholders(&payload, &variables),
+            vec!["${nope}"]
+        );
+    }
+}